@@ -0,0 +1,134 @@
+// Command-line options for where the game starts, so switching between a menu start and an in-game
+// start doesn't mean editing main.rs. Parsed once in main and inserted as the LaunchOptions
+// resource; the plugins that care about an option read it from there:
+// - state, load_slot: manage_state_plugin (the state switched to at startup)
+// - map, pos, facing: ingame_state_plugin (where a new game starts - map is set by --replay too)
+// - config: exposed_config_plugin (which config file is read and written back)
+// - record, replay: explore_plugin::recording (recording movement input, or playing it back)
+
+use bevy::prelude::Resource;
+
+use crate::plugins::explore_plugin::movement::CardinalDirection;
+
+
+pub const USAGE: &str = "\
+usage: dcrawler [options]
+    --state <intro|menu|ingame>   state to start in (default: intro)
+    --map <file>                  map a new game starts on, e.g. config/maps/test.ron
+    --pos <x,y>                   cell a new game starts on (x is the column, y the row)
+    --facing <N|NE|E|SE|S|SW|W|NW>
+                                  direction a new game starts facing (diagonals only on maps
+                                  with eight-way grids)
+    --load-slot <n>               load manual save slot n (as numbered in the load menu) and start
+                                  in game
+    --config <file>               config file to use instead of config/game_config.ron
+    --record <file>               record the movement input of a new game to file
+    --replay <file>               play back a recording instead of taking movement input, starting
+                                  in game on the recording's map
+    --help                        print this message";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LaunchState {
+    Intro,
+    Menu,
+    InGame
+}
+
+#[derive(Resource, Debug, Clone, Default)]
+pub struct LaunchOptions {
+    pub state: Option<LaunchState>,
+    pub map: Option<String>,
+    /// (row, column), the same way map cells are addressed
+    pub pos: Option<(i32, i32)>,
+    pub facing: Option<CardinalDirection>,
+    /// The manual slot index (so one less than the number given on the command line)
+    pub load_slot: Option<u8>,
+    pub config: Option<String>,
+    pub record: Option<String>,
+    pub replay: Option<String>
+}
+
+impl LaunchOptions {
+    /// The state to start in - loading a save or playing back a recording implies starting in game
+    pub fn start_state(&self) -> LaunchState {
+        match (self.state, self.load_slot.is_some() || self.replay.is_some()) {
+            (Some(state), _) => state,
+            (None, true) => LaunchState::InGame,
+            (None, false) => LaunchState::Intro,
+        }
+    }
+}
+
+/// args excludes the program name. Err holds the message to print (USAGE for --help).
+pub fn parse_launch_options(args: impl IntoIterator<Item = String>) -> Result<LaunchOptions, String> {
+    let mut options = LaunchOptions::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            return Err(String::from(USAGE));
+        }
+        let value = match args.next() {
+            Some(v) => v,
+            None => return Err(format!("{} needs a value\n{}", arg, USAGE)),
+        };
+        match arg.as_str() {
+            "--state" => options.state = Some(parse_state(&value)?),
+            "--map" => options.map = Some(value),
+            "--pos" => options.pos = Some(parse_pos(&value)?),
+            "--facing" => options.facing = Some(parse_facing(&value)?),
+            "--load-slot" => options.load_slot = Some(parse_slot(&value)?),
+            "--config" => options.config = Some(value),
+            "--record" => options.record = Some(value),
+            "--replay" => options.replay = Some(value),
+            _ => return Err(format!("unknown option {}\n{}", arg, USAGE)),
+        }
+    }
+    Ok(options)
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn parse_state(value: &str) -> Result<LaunchState, String> {
+    match value.to_lowercase().as_str() {
+        "intro" => Ok(LaunchState::Intro),
+        "menu" => Ok(LaunchState::Menu),
+        "ingame" => Ok(LaunchState::InGame),
+        _ => Err(format!("--state: expected intro, menu or ingame, got {}", value)),
+    }
+}
+
+// "x,y" -> (row, column)
+fn parse_pos(value: &str) -> Result<(i32, i32), String> {
+    let parsed = value
+        .split_once(',')
+        .and_then(|(x, y)| Some((x.trim().parse::<i32>().ok()?, y.trim().parse::<i32>().ok()?)));
+    match parsed {
+        Some((x, y)) => Ok((y, x)),
+        None => Err(format!("--pos: expected x,y, got {}", value)),
+    }
+}
+
+fn parse_facing(value: &str) -> Result<CardinalDirection, String> {
+    match value.to_lowercase().as_str() {
+        "n" | "north" => Ok(CardinalDirection::North),
+        "ne" | "northeast" => Ok(CardinalDirection::NorthEast),
+        "e" | "east" => Ok(CardinalDirection::East),
+        "se" | "southeast" => Ok(CardinalDirection::SouthEast),
+        "s" | "south" => Ok(CardinalDirection::South),
+        "sw" | "southwest" => Ok(CardinalDirection::SouthWest),
+        "w" | "west" => Ok(CardinalDirection::West),
+        "nw" | "northwest" => Ok(CardinalDirection::NorthWest),
+        _ => Err(format!("--facing: expected N, NE, E, SE, S, SW, W or NW, got {}", value)),
+    }
+}
+
+// slots are numbered from 1 in the load menu, and stored from 0
+fn parse_slot(value: &str) -> Result<u8, String> {
+    match value.parse::<u8>() {
+        Ok(n) if n >= 1 => Ok(n - 1),
+        _ => Err(format!("--load-slot: expected a slot number from 1, got {}", value)),
+    }
+}
@@ -0,0 +1,85 @@
+//! dcrawl, a dungeon crawler built on Bevy, as a library.
+//!
+//! The game itself is DcrawlPlugins added to an App (see main.rs, which does little else). Tools
+//! that don't run the game - an editor, a batch map validator - can use the pieces directly: the
+//! map loader, the movement queue, movement recordings and the config file reader are re-exported
+//! below, and what is re-exported here is kept stable. Everything else is reachable through
+//! plugins, but may move.
+//!
+//! Paths to config files (e.g. DEFAULT_MAP_FILEPATH) are relative to the working directory, so
+//! tools should run from the root of the game's folder.
+
+use bevy::{ app::PluginGroupBuilder, prelude::* };
+
+pub mod launch_options;
+pub mod plugins;
+pub mod reflect_ron;
+#[cfg(test)]
+mod tests;
+
+use crate::plugins::{
+    camera_plugin::CameraPlugin,
+    manage_state_plugin::ManageStatePlugin,
+    save_plugin::SavePlugin,
+    transition_plugin::TransitionPlugin,
+    ui_plugin::UiPlugin,
+};
+
+pub use crate::{
+    launch_options::{ LaunchOptions, LaunchState, parse_launch_options },
+    plugins::{
+        exposed_config_plugin::{
+            AutosaveSettings, ControllerBindings, ExposedConfig, ExposedConfigPlugin, KeyboardBindings,
+            read_exposed_config_file, update_exposed_config_file,
+        },
+        explore_plugin::{
+            ExplorePlugin,
+            continuous::MovementMode,
+            map::{
+                CurrentMap, DEFAULT_CELL_SIZE, DEFAULT_MAP_FILEPATH, Grid, MAPS_DIRPATH, MapDefinition,
+                MapNpc, MapTrigger, ShopDefinition, TileKind, TileTrigger,
+                current_grid, list_map_files, load_map_file,
+            },
+            movement::{
+                CardinalDirection, DirectionSet, ExplorationMovementData, ExplorationMovements,
+                MovementStepCompleted, QueueInput, cardinal_direction_angle,
+            },
+            recording::{ ExplorationRecording, RecordedInput, RngSeeds, read_recording, write_recording },
+        },
+    },
+};
+
+
+/// Every plugin the game is made of. Add it after DefaultPlugins:
+///
+/// ```no_run
+/// use bevy::prelude::*;
+/// use dcrawler::DcrawlPlugins;
+///
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(DcrawlPlugins)
+///     .run();
+/// ```
+///
+/// Without a window, MinimalPlugins with AssetPlugin, StatesPlugin and InputPlugin is enough, with
+/// the asset types the renderer would register (Image, Mesh, StandardMaterial, Font) initialized by
+/// hand - see the tests' harness. The game starts in the state LaunchOptions says; insert one before
+/// running to start somewhere other than the intro. Single plugins can be left out with
+/// `DcrawlPlugins.build().disable::<SavePlugin>()`.
+///
+/// The developer tools (debug_overlay_plugin, dev_console_plugin) aren't part of the group; they
+/// are added by main.rs when their features are enabled.
+pub struct DcrawlPlugins;
+
+impl PluginGroup for DcrawlPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(CameraPlugin)
+            .add(ExposedConfigPlugin)
+            .add(SavePlugin)
+            .add(TransitionPlugin)
+            .add(UiPlugin)
+            .add(ManageStatePlugin)
+    }
+}
@@ -1,35 +1,27 @@
-use bevy::{
-    prelude::*,
-    render::renderer::RenderDevice
-};
+use bevy::prelude::*;
 
-mod plugins;
-
-use crate::plugins::{
-    manage_state_plugin::{ ManageStatePlugin, intro_screen_plugin::setup_intro_screen },
-    camera_plugin::{ CameraPlugin, setup_ui_camera },
-    exposed_config_plugin::ExposedConfigPlugin,
-};
+use dcrawler::{ DcrawlPlugins, parse_launch_options };
 
 
 fn main() {
-    App::new()
+    let launch_options = match parse_launch_options(std::env::args().skip(1)) {
+        Ok(o) => o,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    let mut app = App::new();
+    app
+        .insert_resource(launch_options)
         .add_plugins(DefaultPlugins)
-        .add_plugins((
-            CameraPlugin,
-            ExposedConfigPlugin
-        ))
-        .add_plugins(ManageStatePlugin { start_ingame: true })
-        .add_systems(Startup, (
-            // log_render_device_features,
+        .add_plugins(DcrawlPlugins);
 
-            setup_ui_camera, 
-            setup_intro_screen
-        ).chain())
-        .run();
-}
+    #[cfg(feature = "debug_overlay")]
+    app.add_plugins(dcrawler::plugins::debug_overlay_plugin::DebugOverlayPlugin);
+    #[cfg(feature = "dev_console")]
+    app.add_plugins(dcrawler::plugins::dev_console_plugin::DevConsolePlugin);
 
-fn log_render_device_features(_render_device: Res<RenderDevice>) {
-    // info!("{:?}", render_device.features());
+    app.run();
 }
-
@@ -2,3 +2,11 @@ pub mod manage_state_plugin;
 pub mod camera_plugin;
 pub mod explore_plugin;
 pub mod exposed_config_plugin;
+pub mod save_plugin;
+pub mod dialogue_plugin;
+pub mod ui_plugin;
+pub mod transition_plugin;
+#[cfg(feature = "debug_overlay")]
+pub mod debug_overlay_plugin;
+#[cfg(feature = "dev_console")]
+pub mod dev_console_plugin;
@@ -7,7 +7,7 @@ pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_runtime_camera); 
+        app.add_systems(Startup, (setup_ui_camera, setup_runtime_camera));
     }
 }
 
@@ -23,7 +23,6 @@ pub struct NavigateCamera;
 pub const UI_RL: RenderLayers = RenderLayers::layer(1);
 
 
-// THIS FUNCTION GETS CALLED ON STARTUP SCHEDULE IN MAIN.RS
 // order: this camera should render its results "on top of" results of cameras with lower order
 pub fn setup_ui_camera(mut commands: Commands) {
     commands.spawn((
@@ -0,0 +1,185 @@
+// A developer HUD in the top left corner, toggled with TOGGLE_KEY, showing what movement and
+// state changes are doing without having to read the log: the current states, the camera's
+// transform, grid cell and facing, the movement queue and its timers, and FPS.
+//
+// Only built with the debug_overlay cargo feature (cargo run --features debug_overlay), so release
+// builds leave it out. The toggle key is fixed rather than an ExposedConfig binding, so it doesn't
+// show up among the player's controls.
+
+use std::fmt::Write;
+use bevy::{
+    diagnostic::{ DiagnosticsStore, FrameTimeDiagnosticsPlugin },
+    prelude::*,
+};
+
+use crate::plugins::{
+    camera_plugin::NavigateCamera,
+    explore_plugin::{ map::{ CurrentMap, current_grid }, movement::ExplorationMovementData },
+    manage_state_plugin::{ GameModeState, InGameSubstate },
+    ui_plugin::theme::UiTheme,
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const SHOWN_AT_START: bool = false;
+const BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.7);
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        // PostStartup, so the UI camera has been spawned
+        app.add_systems(PostStartup, setup_debug_overlay);
+        app.add_systems(Update, (toggle_debug_overlay, update_debug_overlay).chain());
+    }
+}
+
+
+/////////////////////////////////////////
+// NODE STRUCTURE
+
+#[derive(Component)]
+struct DebugOverlayRootNode;
+
+#[derive(Component)]
+struct DebugOverlayText;
+
+fn setup_debug_overlay(
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let ui_camera = match camera_query.single() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    commands.spawn((
+        DebugOverlayRootNode,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.),
+            left: Val::Px(4.),
+            padding: UiRect::all(Val::Px(6.)),
+            ..default()
+        },
+        BackgroundColor(BACKGROUND),
+        match SHOWN_AT_START {
+            true => Visibility::Visible,
+            false => Visibility::Hidden,
+        },
+        // above the menus, but below the developer console and the transition cover
+        GlobalZIndex(i32::MAX - 2),
+        UiTargetCamera(ui_camera),
+        children![(
+            DebugOverlayText,
+            Text::default(),
+            TextFont {
+                font: theme.fonts.font_handle.clone(),
+                font_size: theme.fonts.small_size,
+                ..default()
+            },
+            TextColor(theme.colors.text)
+        )]
+    ));
+}
+
+fn toggle_debug_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut root_query: Query<&mut Visibility, With<DebugOverlayRootNode>>
+) {
+    if !keyboard_input.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    for mut visibility in &mut root_query {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+// Only rebuilt while shown
+fn update_debug_overlay(
+    root_query: Query<&Visibility, With<DebugOverlayRootNode>>,
+    mut text_query: Query<&mut Text, With<DebugOverlayText>>,
+    game_mode_state: Res<State<GameModeState>>,
+    ingame_substate: Option<Res<State<InGameSubstate>>>,
+    camera_query: Query<&Transform, With<NavigateCamera>>,
+    current_map: Option<Res<CurrentMap>>,
+    movement_data: Res<ExplorationMovementData>,
+    diagnostics: Res<DiagnosticsStore>
+) {
+    if !root_query.iter().any(|visibility| *visibility != Visibility::Hidden) {
+        return;
+    }
+    let mut text = match text_query.single_mut() {
+        Ok(t) => t,
+        Err(_) => return,
+    };
+
+    let mut lines = String::new();
+
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed());
+    match fps {
+        Some(fps) => writeln!(lines, "FPS: {:.0}", fps),
+        None => writeln!(lines, "FPS: -"),
+    }.ok();
+
+    writeln!(lines, "GameModeState: {:?}", game_mode_state.get()).ok();
+    match ingame_substate {
+        Some(substate) => writeln!(lines, "InGameSubstate: {:?}", substate.get()),
+        None => writeln!(lines, "InGameSubstate: -"),
+    }.ok();
+
+    if let Ok(transform) = camera_query.single() {
+        let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+        writeln!(lines, "Camera translation: {:.2}", transform.translation).ok();
+        writeln!(lines, "Camera yaw: {:.1} deg, pitch: {:.1} deg", yaw.to_degrees(), pitch.to_degrees()).ok();
+        let (row, column) = current_grid(current_map.as_deref()).world_to_cell(transform.translation);
+        writeln!(lines, "Cell (row, column): ({}, {})", row, column).ok();
+    }
+
+    writeln!(lines, "Cardinal facing: {:?}", movement_data.cardinal_facing).ok();
+    writeln!(lines, "Oriented to cardinal directions: {}", movement_data.oriented_to_cardinal_directions).ok();
+    writeln!(lines, "Free looking: {}", movement_data.free_looking).ok();
+    writeln!(lines, "Movement timer: {}", timer_progress(movement_data.current_movement_timer.as_ref())).ok();
+    writeln!(lines, "Input buffer: {:?}", movement_data.input_buffer).ok();
+    match &movement_data.held_movement {
+        Some(held) => writeln!(lines, "Held: {:?}, repeat in {}", held.movement, timer_progress(Some(&held.repeat_timer))),
+        None => writeln!(lines, "Held: -"),
+    }.ok();
+    write!(lines, "Command queue ({}): {:?}", movement_data.command_queue.len(), movement_data.command_queue).ok();
+
+    if text.0 != lines {
+        text.0 = lines;
+    }
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+// "0.12/0.30s (40%)", or "-" when there is no timer running
+fn timer_progress(timer: Option<&Timer>) -> String {
+    match timer {
+        Some(timer) => format!(
+            "{:.2}/{:.2}s ({:.0}%)",
+            timer.elapsed_secs(),
+            timer.duration().as_secs_f32(),
+            timer.fraction() * 100.
+        ),
+        None => String::from("-"),
+    }
+}
@@ -0,0 +1,283 @@
+// A drop-down developer console, opened and closed with TOGGLE_KEY (Escape also closes it). Type a
+// command and press Enter to run it; Up and Down step through earlier commands, and Tab completes
+// command names and their arguments (maps, items, config paths). "help" lists the commands - see
+// dev_console_plugin/commands.rs.
+//
+// While the console is open it takes the keyboard: key presses are cleared before anything else
+// reads them, so typing doesn't also walk or open menus.
+//
+// Only built with the dev_console cargo feature (cargo run --features dev_console).
+
+use bevy::{
+    input::{ ButtonState, InputSystems, keyboard::{ Key, KeyboardInput } },
+    prelude::*,
+    ui::FocusPolicy,
+};
+
+mod commands;
+mod config_path;
+
+use crate::plugins::{
+    dev_console_plugin::commands::{ ConsoleCommand, completions, parse_command, run_command },
+    exposed_config_plugin::{ ActionInput, ActionInputSystems, collect_action_input },
+    ui_plugin::theme::UiTheme,
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+const LOG_LINES_SHOWN: usize = 14;
+const MAX_LOG_LINES: usize = 200;
+const MAX_HISTORY: usize = 50;
+const BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.85);
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+pub struct DevConsolePlugin;
+
+impl Plugin for DevConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DevConsole>();
+        app.add_systems(
+            PreUpdate,
+            (
+                console_keyboard_input.after(InputSystems).before(ActionInputSystems),
+                block_actions_while_open.in_set(ActionInputSystems).after(collect_action_input)
+            )
+        );
+        app.add_systems(Update, draw_console);
+    }
+}
+
+
+/////////////////////////////////////////
+// CONSOLE STATE
+
+#[derive(Resource, Default)]
+struct DevConsole {
+    open: bool,
+    input: String,
+    log: Vec<String>,
+    // oldest first
+    history: Vec<String>,
+    // which history entry Up/Down has brought back, if any
+    history_index: Option<usize>
+}
+
+impl DevConsole {
+    fn print(&mut self, lines: impl IntoIterator<Item = String>) {
+        self.log.extend(lines);
+        let excess = self.log.len().saturating_sub(MAX_LOG_LINES);
+        self.log.drain(..excess);
+    }
+
+    fn remember(&mut self, line: &str) {
+        if self.history.last().map(String::as_str) != Some(line) {
+            self.history.push(line.to_string());
+        }
+        let excess = self.history.len().saturating_sub(MAX_HISTORY);
+        self.history.drain(..excess);
+        self.history_index = None;
+    }
+
+    fn step_history(&mut self, back: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        self.history_index = match (self.history_index, back) {
+            (None, true) => Some(self.history.len() - 1),
+            (None, false) => None,
+            (Some(index), true) => Some(index.saturating_sub(1)),
+            (Some(index), false) if index + 1 < self.history.len() => Some(index + 1),
+            (Some(_), false) => None,
+        };
+        self.input = match self.history_index {
+            Some(index) => self.history[index].clone(),
+            None => String::new(),
+        };
+    }
+}
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+fn console_keyboard_input(
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    mut console: ResMut<DevConsole>,
+    mut commands: Commands
+) {
+    let was_open = console.open;
+
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if event.key_code == TOGGLE_KEY {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Escape => console.open = false,
+            Key::Enter => {
+                let line = console.input.trim().to_string();
+                console.input.clear();
+                if !line.is_empty() {
+                    console.remember(&line);
+                    console.print([format!("> {}", line)]);
+                    commands.queue(move |world: &mut World| run_console_line(world, &line));
+                }
+            },
+            Key::Tab => {
+                commands.queue(complete_console_input);
+            },
+            Key::ArrowUp => console.step_history(true),
+            Key::ArrowDown => console.step_history(false),
+            Key::Backspace => {
+                console.input.pop();
+            },
+            Key::Space => console.input.push(' '),
+            Key::Character(typed) => {
+                console.input.extend(typed.chars().filter(|c| !c.is_control()));
+            },
+            _ => {}
+        }
+    }
+
+    // the key that closed the console shouldn't reach the game either
+    if console.open || was_open {
+        keyboard_input.reset_all();
+    }
+}
+
+// Gamepad actions still come through the keyboard being cleared
+fn block_actions_while_open(console: Res<DevConsole>, mut action_input: ResMut<ActionInput>) {
+    if console.open {
+        action_input.clear();
+    }
+}
+
+fn run_console_line(world: &mut World, line: &str) {
+    let output = match parse_command(line) {
+        Ok(ConsoleCommand::Clear) => {
+            world.resource_mut::<DevConsole>().log.clear();
+            return;
+        },
+        Ok(command) => run_command(world, command),
+        Err(e) => Err(e),
+    };
+    let lines = match output {
+        Ok(lines) => lines,
+        Err(e) => vec![e],
+    };
+    world.resource_mut::<DevConsole>().print(lines);
+}
+
+// One match replaces the input; several fill in what they have in common and are listed
+fn complete_console_input(world: &mut World) {
+    let input = world.resource::<DevConsole>().input.clone();
+    let candidates = completions(world, &input);
+    let mut console = world.resource_mut::<DevConsole>();
+
+    match candidates.as_slice() {
+        [] => {},
+        [only] => console.input = format!("{} ", only),
+        _ => {
+            let common = candidates.iter().skip(1).fold(candidates[0].clone(), |common, candidate| {
+                common.chars()
+                    .zip(candidate.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect()
+            });
+            if common.len() > input.len() {
+                console.input = common;
+            }
+            console.print(candidates);
+        },
+    }
+}
+
+
+/////////////////////////////////////////
+// NODE STRUCTURE
+
+#[derive(Component)]
+struct DevConsoleRootNode;
+
+#[derive(Component)]
+struct DevConsoleLogText;
+
+#[derive(Component)]
+struct DevConsoleInputText;
+
+fn draw_console(
+    console: Res<DevConsole>,
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    root_query: Query<Entity, With<DevConsoleRootNode>>,
+    mut log_query: Query<&mut Text, (With<DevConsoleLogText>, Without<DevConsoleInputText>)>,
+    mut input_query: Query<&mut Text, (With<DevConsoleInputText>, Without<DevConsoleLogText>)>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    if !console.open {
+        for root in &root_query {
+            commands.entity(root).despawn();
+        }
+        return;
+    }
+
+    let shown_log = console.log[console.log.len().saturating_sub(LOG_LINES_SHOWN)..].join("\n");
+    let shown_input = format!("> {}_", console.input);
+
+    if root_query.is_empty() {
+        let ui_camera = match camera_query.single() {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+        let text_font = TextFont {
+            font: theme.fonts.font_handle.clone(),
+            font_size: theme.fonts.small_size,
+            ..default()
+        };
+        commands.spawn((
+            DevConsoleRootNode,
+            Node {
+                width: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(theme.sizes.padding * 0.5)),
+                row_gap: Val::Px(theme.sizes.gap * 0.5),
+                ..default()
+            },
+            BackgroundColor(BACKGROUND),
+            FocusPolicy::Block,
+            // above the menus and the debug overlay, but below the transition cover
+            GlobalZIndex(i32::MAX - 1),
+            UiTargetCamera(ui_camera),
+            children![
+                (DevConsoleLogText, Text::new(shown_log), text_font.clone(), TextColor(theme.colors.text)),
+                (DevConsoleInputText, Text::new(shown_input), text_font, TextColor(theme.colors.title_text))
+            ]
+        ));
+        return;
+    }
+
+    if !console.is_changed() {
+        return;
+    }
+    if let Ok(mut text) = log_query.single_mut() {
+        text.0 = shown_log;
+    }
+    if let Ok(mut text) = input_query.single_mut() {
+        text.0 = shown_input;
+    }
+}
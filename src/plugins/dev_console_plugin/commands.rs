@@ -0,0 +1,327 @@
+/// Parsing, completing and running the developer console's commands.
+///
+/// Commands run with the whole World, as between them they touch most of the game's resources.
+/// Each returns the lines to print, or an error to print instead.
+use bevy::{ prelude::*, reflect::TypeRegistry };
+
+use crate::plugins::{
+    camera_plugin::NavigateCamera,
+    dev_console_plugin::config_path::{ get_path, leaf_paths, set_path },
+    explore_plugin::{
+        map::{ CurrentMap, MapEntered, TileKind, list_map_files, load_map_file },
+        movement::{ CardinalDirection, ExplorationMovementData, cardinal_direction_angle },
+    },
+    exposed_config_plugin::{ ExposedConfig, read_exposed_config_file },
+    manage_state_plugin::{
+        GameModeState, InGameSubstate,
+        ingame_state_plugin::{
+            InGameData,
+            party::{ DEFAULT_PARTY_FILEPATH, InventoryItem, PartyMember, load_party_file },
+        },
+    },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const HELP: [&str; 11] = [
+    "teleport <x> <y>          move to the cell in column x, row y",
+    "face <direction>          turn north, northeast, east, ... or northwest",
+    "state <intro|menu|creation|load|ingame|explore|shop>",
+    "load_map <file>           enter a map, staying on the same cell",
+    "give <item> [n]           add n (default 1) of item to the party leader's inventory",
+    "set flag <key> <value>    set a story flag",
+    "reload config             read the config file again",
+    "get <path>                show a config value, e.g. get autosave.every_n_steps",
+    "set <path> <value>        set a config value (as RON) until the game is closed, e.g.",
+    "                          set keyboard_bindings.exploration_controls.Menu Escape",
+    "clear, help"
+];
+const COMMAND_NAMES: [&str; 10] = [
+    "clear", "face", "get", "give", "help", "load_map", "reload", "set", "state", "teleport"
+];
+const DIRECTION_NAMES: [&str; 8] = [
+    "north", "northeast", "east", "southeast", "south", "southwest", "west", "northwest"
+];
+const STATE_NAMES: [&str; 7] = ["intro", "menu", "creation", "load", "ingame", "explore", "shop"];
+
+
+/////////////////////////////////////////
+// PARSING
+
+#[derive(Debug, PartialEq)]
+pub(super) enum ConsoleCommand {
+    Help,
+    Clear,
+    /// (row, column), the same way map cells are addressed
+    Teleport((i32, i32)),
+    Face(CardinalDirection),
+    State(StateTarget),
+    LoadMap(String),
+    Give { item: String, count: u32 },
+    SetFlag { key: String, value: String },
+    ReloadConfig,
+    Get { path: String },
+    Set { path: String, value: String }
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) enum StateTarget {
+    GameMode(GameModeState),
+    InGame(InGameSubstate)
+}
+
+pub(super) fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (name, args) = match words.split_first() {
+        Some((name, args)) => (*name, args),
+        None => return Err(String::from("type help for a list of commands")),
+    };
+
+    match (name, args) {
+        ("help", []) => Ok(ConsoleCommand::Help),
+        ("clear", []) => Ok(ConsoleCommand::Clear),
+        ("teleport", [x, y]) => match (x.parse::<i32>(), y.parse::<i32>()) {
+            (Ok(x), Ok(y)) => Ok(ConsoleCommand::Teleport((y, x))),
+            _ => Err(format!("teleport: expected whole numbers, got {} {}", x, y)),
+        },
+        ("face", [direction]) => parse_direction(direction).map(ConsoleCommand::Face),
+        ("state", [state]) => parse_state(state).map(ConsoleCommand::State),
+        ("load_map", [file]) => Ok(ConsoleCommand::LoadMap(file.to_string())),
+        ("give", [item @ .., count]) if !item.is_empty() && count.parse::<u32>().is_ok() => Ok(ConsoleCommand::Give {
+            item: item.join(" "),
+            count: count.parse().unwrap_or(1)
+        }),
+        ("give", [_, ..]) => Ok(ConsoleCommand::Give { item: args.join(" "), count: 1 }),
+        ("set", ["flag", key, value @ ..]) if !value.is_empty() => Ok(ConsoleCommand::SetFlag {
+            key: key.to_string(),
+            value: value.join(" ")
+        }),
+        // the path may hold map keys with spaces in, so only the last word is the value
+        ("set", [path @ .., value]) if !path.is_empty() => Ok(ConsoleCommand::Set {
+            path: path.join(" "),
+            value: value.to_string()
+        }),
+        ("reload", ["config"]) => Ok(ConsoleCommand::ReloadConfig),
+        ("get", [_, ..]) => Ok(ConsoleCommand::Get { path: args.join(" ") }),
+        _ if COMMAND_NAMES.contains(&name) => Err(format!("{}: wrong arguments, type help for usage", name)),
+        _ => Err(format!("unknown command {}, type help for a list of commands", name)),
+    }
+}
+
+
+/////////////////////////////////////////
+// RUNNING
+
+pub(super) fn run_command(world: &mut World, command: ConsoleCommand) -> Result<Vec<String>, String> {
+    match command {
+        ConsoleCommand::Help => Ok(HELP.iter().map(|line| line.to_string()).collect()),
+        // handled by the console itself
+        ConsoleCommand::Clear => Ok(Vec::new()),
+        ConsoleCommand::Teleport(cell) => teleport(world, cell),
+        ConsoleCommand::Face(direction) => face(world, direction),
+        ConsoleCommand::State(target) => {
+            match target {
+                StateTarget::GameMode(state) => world.resource_mut::<NextState<GameModeState>>().set(state),
+                StateTarget::InGame(substate) => match world.get_resource_mut::<NextState<InGameSubstate>>() {
+                    Some(mut next_state) => next_state.set(substate),
+                    None => return Err(String::from("state: explore and shop are only reachable in game")),
+                },
+            }
+            Ok(Vec::new())
+        },
+        ConsoleCommand::LoadMap(filepath) => load_map(world, &filepath),
+        ConsoleCommand::Give { item, count } => give(world, &item, count),
+        ConsoleCommand::SetFlag { key, value } => {
+            let mut ingame_data = world
+                .get_resource_mut::<InGameData>()
+                .ok_or_else(|| String::from("set flag: not in game"))?;
+            ingame_data.flags.insert(key.clone(), value.clone());
+            Ok(vec![format!("{} = {}", key, value)])
+        },
+        ConsoleCommand::ReloadConfig => {
+            let filepath = world.resource::<ExposedConfig>().filepath.clone();
+            let config = read_exposed_config_file(&filepath)?;
+            world.insert_resource(config);
+            Ok(vec![format!("reloaded {}", filepath)])
+        },
+        ConsoleCommand::Get { path } => {
+            let value = get_path(world.resource::<ExposedConfig>(), &path, &config_type_registry())?;
+            Ok(vec![format!("{} = {}", path, value)])
+        },
+        ConsoleCommand::Set { path, value } => {
+            let mut config = world.resource_mut::<ExposedConfig>();
+            set_path(&mut *config, &path, &value, &config_type_registry())?;
+            Ok(vec![format!("{} = {}", path, value)])
+        },
+    }
+}
+
+fn teleport(world: &mut World, cell: (i32, i32)) -> Result<Vec<String>, String> {
+    let map = &world
+        .get_resource::<CurrentMap>()
+        .ok_or_else(|| String::from("teleport: not in game"))?
+        .definition;
+    let (tile, grid) = (map.tile_at(cell), map.grid);
+    let note = match tile {
+        None => return Err(format!("teleport: ({}, {}) is outside the map", cell.1, cell.0)),
+        Some(TileKind::Inaccessible) => " (an inaccessible tile)",
+        Some(_) => "",
+    };
+
+    drop_movement(world);
+    let mut camera_query = world.query_filtered::<&mut Transform, With<NavigateCamera>>();
+    let mut camera_transform = camera_query
+        .single_mut(world)
+        .map_err(|_| String::from("teleport: no camera"))?;
+    camera_transform.translation = grid.cell_to_world(cell, camera_transform.translation.y);
+    Ok(vec![format!("teleported to ({}, {}){}", cell.1, cell.0, note)])
+}
+
+fn face(world: &mut World, direction: CardinalDirection) -> Result<Vec<String>, String> {
+    drop_movement(world);
+    let mut camera_query = world.query_filtered::<&mut Transform, With<NavigateCamera>>();
+    let mut camera_transform = camera_query
+        .single_mut(world)
+        .map_err(|_| String::from("face: no camera"))?;
+    camera_transform.rotation = Quat::from_rotation_y(cardinal_direction_angle(direction));
+
+    if let Some(mut movement_data) = world.get_resource_mut::<ExplorationMovementData>() {
+        movement_data.cardinal_facing = Some(direction);
+        movement_data.oriented_to_cardinal_directions = true;
+    }
+    Ok(vec![format!("facing {:?}", direction)])
+}
+
+// Entering a map this way counts as arriving on it, so MapEntered fires its autosave
+fn load_map(world: &mut World, filepath: &str) -> Result<Vec<String>, String> {
+    if world.get_resource::<CurrentMap>().is_none() {
+        return Err(String::from("load_map: not in game"));
+    }
+    let map = load_map_file(filepath)?;
+    let name = map.definition.name.clone();
+    world.insert_resource(map);
+    world.write_message(MapEntered { filepath: filepath.to_string() });
+    Ok(vec![format!("entered {}", name)])
+}
+
+fn give(world: &mut World, item: &str, count: u32) -> Result<Vec<String>, String> {
+    let mut ingame_data = world
+        .get_resource_mut::<InGameData>()
+        .ok_or_else(|| String::from("give: not in game"))?;
+    let leader = ingame_data.party
+        .first_mut()
+        .ok_or_else(|| String::from("give: the party is empty"))?;
+
+    match leader.inventory.iter_mut().find(|owned| owned.name == item) {
+        Some(owned) => owned.count += count,
+        None => leader.inventory.push(InventoryItem { name: item.to_string(), count }),
+    }
+    Ok(vec![format!("gave {} x{} to {}", item, count, leader.name)])
+}
+
+
+/////////////////////////////////////////
+// COMPLETION
+
+/// The whole lines the input could be completed to
+pub(super) fn completions(world: &mut World, input: &str) -> Vec<String> {
+    let (name, arg) = match input.split_once(' ') {
+        Some((name, arg)) => (name, arg),
+        None => return matching(COMMAND_NAMES.iter().map(|c| c.to_string()), "", input),
+    };
+
+    let candidates: Vec<String> = match name {
+        "face" => DIRECTION_NAMES.iter().map(|d| d.to_string()).collect(),
+        "state" => STATE_NAMES.iter().map(|s| s.to_string()).collect(),
+        "load_map" => list_map_files(),
+        "give" => known_items(world),
+        "reload" => vec![String::from("config")],
+        "get" | "set" => {
+            let mut paths = leaf_paths(world.resource::<ExposedConfig>());
+            if name == "set" {
+                paths.insert(0, String::from("flag"));
+            }
+            paths
+        },
+        _ => Vec::new(),
+    };
+    matching(candidates, &format!("{} ", name), arg)
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn config_type_registry() -> TypeRegistry {
+    let mut type_registry = TypeRegistry::default();
+    type_registry.register::<ExposedConfig>();
+    type_registry
+}
+
+// Whatever was moving stops, so it doesn't carry on from the new place
+fn drop_movement(world: &mut World) {
+    if let Some(mut movement_data) = world.get_resource_mut::<ExplorationMovementData>() {
+        movement_data.current_movement_timer = None;
+        movement_data.current_movement_command = None;
+        movement_data.command_queue.clear();
+        movement_data.input_buffer.clear();
+        movement_data.held_movement = None;
+    }
+}
+
+// Items the game knows of: those the party has, and those in the default party
+fn known_items(world: &mut World) -> Vec<String> {
+    let mut party: Vec<PartyMember> = world
+        .get_resource::<InGameData>()
+        .map(|ingame_data| ingame_data.party.clone())
+        .unwrap_or_default();
+    if let Ok(default_party) = load_party_file(DEFAULT_PARTY_FILEPATH) {
+        party.extend(default_party.members);
+    }
+
+    let mut items: Vec<String> = party.iter()
+        .flat_map(|member| {
+            member.inventory.iter().map(|item| item.name.clone())
+                .chain(member.equipment.iter().filter_map(|slot| slot.item.clone()))
+        })
+        .collect();
+    items.sort();
+    items.dedup();
+    items
+}
+
+fn matching(candidates: impl IntoIterator<Item = String>, prefix: &str, typed: &str) -> Vec<String> {
+    candidates.into_iter()
+        .filter(|candidate| candidate.starts_with(typed))
+        .map(|candidate| format!("{}{}", prefix, candidate))
+        .collect()
+}
+
+fn parse_direction(value: &str) -> Result<CardinalDirection, String> {
+    match value.to_lowercase().as_str() {
+        "n" | "north" => Ok(CardinalDirection::North),
+        "ne" | "northeast" => Ok(CardinalDirection::NorthEast),
+        "e" | "east" => Ok(CardinalDirection::East),
+        "se" | "southeast" => Ok(CardinalDirection::SouthEast),
+        "s" | "south" => Ok(CardinalDirection::South),
+        "sw" | "southwest" => Ok(CardinalDirection::SouthWest),
+        "w" | "west" => Ok(CardinalDirection::West),
+        "nw" | "northwest" => Ok(CardinalDirection::NorthWest),
+        _ => Err(format!("face: expected a direction such as north or northeast, got {}", value)),
+    }
+}
+
+fn parse_state(value: &str) -> Result<StateTarget, String> {
+    match value.to_lowercase().as_str() {
+        "intro" => Ok(StateTarget::GameMode(GameModeState::IntroScreen)),
+        "menu" => Ok(StateTarget::GameMode(GameModeState::MainMenu)),
+        "creation" => Ok(StateTarget::GameMode(GameModeState::CharacterCreation)),
+        "load" => Ok(StateTarget::GameMode(GameModeState::LoadGameMenu)),
+        "ingame" => Ok(StateTarget::GameMode(GameModeState::InGame)),
+        "explore" => Ok(StateTarget::InGame(InGameSubstate::Explore)),
+        "shop" => Ok(StateTarget::InGame(InGameSubstate::Shop)),
+        "combat" => Err(String::from("state: combat isn't in the game yet")),
+        _ => Err(format!("state: expected one of {}, got {}", STATE_NAMES.join(", "), value)),
+    }
+}
@@ -0,0 +1,89 @@
+/// Reading and writing fields of a reflected resource (ExposedConfig) by a dotted path, e.g.
+/// "autosave.every_n_steps" or "keyboard_bindings.exploration_controls.Walk Forward".
+///
+/// Each segment is a struct field name or a map key; map keys may contain spaces, so the path is
+/// only ever split on dots. Values are read and written as RON, through the same type registration
+/// the config file is read with (see reflect_ron).
+use bevy::reflect::{ PartialReflect, ReflectMut, ReflectRef, TypeRegistry };
+
+use crate::reflect_ron::{ value_from_ron_str, value_to_ron_string };
+
+
+pub(super) fn get_path(
+    root: &dyn PartialReflect,
+    path: &str,
+    type_registry: &TypeRegistry
+) -> Result<String, String> {
+    let mut value = root;
+    for segment in path.split('.') {
+        value = match value.reflect_ref() {
+            ReflectRef::Struct(s) => s.field(segment),
+            ReflectRef::Map(m) => m.get(&String::from(segment)),
+            _ => None,
+        }.ok_or_else(|| format!("no field {} in {}", segment, path))?;
+    }
+    value_to_ron_string(value, type_registry)
+}
+
+pub(super) fn set_path(
+    root: &mut dyn PartialReflect,
+    path: &str,
+    value_ron: &str,
+    type_registry: &TypeRegistry
+) -> Result<(), String> {
+    let mut value = root;
+    for segment in path.split('.') {
+        value = match value.reflect_mut() {
+            ReflectMut::Struct(s) => s.field_mut(segment),
+            ReflectMut::Map(m) => m.get_mut(&String::from(segment)),
+            _ => None,
+        }.ok_or_else(|| format!("no field {} in {}", segment, path))?;
+    }
+
+    let type_id = value
+        .get_represented_type_info()
+        .ok_or_else(|| format!("{} has no type information", path))?
+        .type_id();
+    let new_value = value_from_ron_str(type_id, value_ron, type_registry)?;
+    value.try_apply(new_value.as_ref()).map_err(|e| e.to_string())
+}
+
+/// Every path that leads to a value rather than to a struct or map, for completion
+pub(super) fn leaf_paths(root: &dyn PartialReflect) -> Vec<String> {
+    let mut paths = Vec::new();
+    collect_leaf_paths(root, String::new(), &mut paths);
+    paths.sort();
+    paths
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn collect_leaf_paths(value: &dyn PartialReflect, prefix: String, paths: &mut Vec<String>) {
+    let join = |segment: &str| match prefix.is_empty() {
+        true => String::from(segment),
+        false => format!("{}.{}", prefix, segment),
+    };
+    match value.reflect_ref() {
+        ReflectRef::Struct(s) => {
+            for index in 0..s.field_len() {
+                if let (Some(name), Some(field)) = (s.name_at(index), s.field_at(index)) {
+                    collect_leaf_paths(field, join(name), paths);
+                }
+            }
+        },
+        ReflectRef::Map(m) => {
+            for (key, field) in m.iter() {
+                if let Some(key) = key.try_downcast_ref::<String>() {
+                    collect_leaf_paths(field, join(key), paths);
+                }
+            }
+        },
+        _ => {
+            if !prefix.is_empty() {
+                paths.push(prefix);
+            }
+        },
+    }
+}
@@ -0,0 +1,220 @@
+// This plugin runs dialogue: branching conversations loaded from RON (see dialogue_plugin/tree.rs)
+// and shown in an overlay at the bottom of the screen (see dialogue_plugin/overlay.rs).
+//
+// Dialogue is started by writing a StartDialogue message. This happens when:
+// - the player steps onto a map cell with a Dialogue trigger (explore_plugin::map)
+// - the player presses "Interact" while facing an NPC (npc_interaction_system below)
+// - the player enters a shop with a greeting dialogue (ingame_state_plugin::shop_substate)
+//
+// While DialogueState is Open, exploration movement input is blocked.
+
+use std::collections::HashMap;
+use bevy::prelude::*;
+
+pub mod overlay;
+pub mod tree;
+
+use crate::plugins::{
+    camera_plugin::NavigateCamera,
+    dialogue_plugin::{
+        overlay::{
+            setup_dialogue_overlay, cleanup_dialogue_overlay,
+            dialogue_typewriter_system, dialogue_controls_system, dialogue_choice_button_system,
+            update_dialogue_overlay,
+        },
+        tree::{ DialogueNode, DialogueTree, load_dialogue_file, first_met_branch },
+    },
+    exposed_config_plugin::ActionInput,
+    explore_plugin::{
+        map::{ CurrentMap, Npc, current_grid },
+        movement::{ ExplorationMovementData, calc_closest_cardinal_dir, cancel_queued_movements },
+    },
+    manage_state_plugin::{
+        CharacterMenuState, DialogueState, GameModeState, InGameSubstate, PauseState,
+        ingame_state_plugin::InGameData,
+    },
+};
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<StartDialogue>();
+
+        app.add_systems(
+            Update,
+            start_dialogue_system.run_if(in_state(GameModeState::InGame))
+        );
+        app.add_systems(
+            Update,
+            npc_interaction_system
+                .run_if(in_state(InGameSubstate::Explore))
+                .run_if(in_state(DialogueState::Closed))
+                .run_if(in_state(CharacterMenuState::Closed))
+                .run_if(in_state(PauseState::Running))
+        );
+
+        app.add_systems(OnEnter(DialogueState::Open), setup_dialogue_overlay);
+        app.add_systems(OnExit(DialogueState::Open), cleanup_dialogue_overlay);
+        app.add_systems(
+            Update,
+            (
+                dialogue_typewriter_system,
+                dialogue_controls_system,
+                dialogue_choice_button_system,
+                update_dialogue_overlay,
+            )
+                .chain()
+                .run_if(in_state(DialogueState::Open))
+                .run_if(in_state(PauseState::Running))
+        );
+    }
+}
+
+
+/////////////////////////////////////////
+// DIALOGUE DATA
+
+/// Open the dialogue tree stored at filepath. Ignored if a dialogue is already open.
+#[derive(Message, Debug)]
+pub struct StartDialogue {
+    pub filepath: String
+}
+
+/// The dialogue currently open, and how far through it the player is
+#[derive(Resource)]
+pub struct ActiveDialogue {
+    tree: DialogueTree,
+    node: String,
+    line_index: usize,
+    revealed_chars: f32,
+    // indices into the node's choices whose conditions were met when the node was entered
+    visible_choices: Vec<usize>,
+    selected_choice: usize
+}
+
+impl ActiveDialogue {
+    fn current_node(&self) -> &DialogueNode {
+        &self.tree.nodes[&self.node]
+    }
+
+    fn current_line(&self) -> &str {
+        self.current_node().lines.get(self.line_index).map(String::as_str).unwrap_or("")
+    }
+
+    fn line_fully_revealed(&self) -> bool {
+        self.revealed_chars as usize >= self.current_line().chars().count()
+    }
+
+    fn on_last_line(&self) -> bool {
+        self.line_index + 1 >= self.current_node().lines.len()
+    }
+
+    fn showing_choices(&self) -> bool {
+        self.on_last_line() && self.line_fully_revealed() && !self.visible_choices.is_empty()
+    }
+
+    /// Move to the named node, applying its flags. Returns false (leaving the dialogue where it
+    /// was) if the tree has no such node.
+    fn enter_node(&mut self, node: &str, flags: &mut HashMap<String, String>) -> bool {
+        let dialogue_node = match self.tree.nodes.get(node) {
+            Some(n) => n,
+            None => {
+                error!("dialogue has no node named {}", node);
+                return false;
+            }
+        };
+
+        flags.extend(dialogue_node.set_flags.clone());
+        self.visible_choices = dialogue_node.choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| choice.condition.as_ref().is_none_or(|c| c.is_met(flags)))
+            .map(|(index, _)| index)
+            .collect();
+        self.node = String::from(node);
+        self.line_index = 0;
+        self.revealed_chars = 0.;
+        self.selected_choice = 0;
+        true
+    }
+}
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+fn start_dialogue_system(
+    mut start_dialogue: MessageReader<StartDialogue>,
+    dialogue_state: Res<State<DialogueState>>,
+    mut next_dialogue_state: ResMut<NextState<DialogueState>>,
+    mut ingame_data: ResMut<InGameData>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    mut commands: Commands,
+) {
+    // only the first request in a frame can open - the state change isn't visible until next frame
+    let mut opened = *dialogue_state.get() == DialogueState::Open;
+
+    for request in start_dialogue.read() {
+        if opened {
+            warn!("dialogue already open, ignoring {}", request.filepath);
+            continue;
+        }
+
+        let tree = match load_dialogue_file(&request.filepath) {
+            Ok(t) => t,
+            Err(e) => {
+                error!("failure loading dialogue: {}", e);
+                continue;
+            }
+        };
+        let start_node = match first_met_branch(&tree.start, &ingame_data.flags) {
+            Some(n) => String::from(n),
+            None => continue,
+        };
+
+        let mut active_dialogue = ActiveDialogue {
+            tree,
+            node: String::new(),
+            line_index: 0,
+            revealed_chars: 0.,
+            visible_choices: Vec::new(),
+            selected_choice: 0
+        };
+        if !active_dialogue.enter_node(&start_node, &mut ingame_data.flags) {
+            continue;
+        }
+
+        cancel_queued_movements(&mut movement_data);
+        commands.insert_resource(active_dialogue);
+        next_dialogue_state.set(DialogueState::Open);
+        opened = true;
+    }
+}
+
+/// "Interact" while facing the cell an NPC stands on starts its dialogue
+fn npc_interaction_system(
+    mut action_input: ResMut<ActionInput>,
+    current_map: Option<Res<CurrentMap>>,
+    camera_transform_q: Single<&Transform, With<NavigateCamera>>,
+    npc_query: Query<&Npc>,
+    mut start_dialogue: MessageWriter<StartDialogue>,
+) {
+    if !action_input.consume("Interact") {
+        return;
+    }
+
+    let camera_transform = camera_transform_q.into_inner();
+    let grid = current_grid(current_map.as_deref());
+    let player_cell = grid.world_to_cell(camera_transform.translation);
+    let facing_offset = calc_closest_cardinal_dir(camera_transform, grid.directions).cell_offset();
+    let faced_cell = (player_cell.0 + facing_offset.0, player_cell.1 + facing_offset.1);
+
+    if let Some(npc) = npc_query.iter().find(|npc| npc.cell == faced_cell) {
+        start_dialogue.write(StartDialogue { filepath: npc.dialogue.clone() });
+    }
+}
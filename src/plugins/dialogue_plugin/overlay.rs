@@ -0,0 +1,267 @@
+/// The dialogue overlay: a panel along the bottom of the screen showing the speaker, the current
+/// line (revealed a character at a time), and the node's choices once the last line is shown.
+///
+/// Controls, resolved through ActionInput:
+/// - "Interact" reveals the rest of the line, moves to the next line, or confirms a choice
+/// - "Walk Forward"/"Walk Backward" move the selected choice up/down
+/// Choices can also be clicked. They are left out of menu focus navigation, but the overlay is still
+/// a FocusScope so the screen beneath can't be navigated while it is open.
+use bevy::prelude::*;
+
+use crate::plugins::{
+    dialogue_plugin::{ ActiveDialogue, tree::first_met_branch },
+    exposed_config_plugin::ActionInput,
+    manage_state_plugin::{ DialogueState, ingame_state_plugin::InGameData },
+    ui_plugin::{
+        focus::{ FocusScope, SkipFocus },
+        theme::UiTheme,
+        widgets::{ self, UiSelected },
+    },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const TYPEWRITER_CHARS_PER_SECOND: f32 = 40.;
+
+
+/////////////////////////////////////////
+// NODE STRUCTURE
+
+#[derive(Component)]
+pub struct DialogueRootNode;
+
+#[derive(Component)]
+pub struct DialogueSpeakerText;
+
+#[derive(Component)]
+pub struct DialogueLineText;
+
+#[derive(Component)]
+pub struct DialogueChoicesNode;
+
+// index into ActiveDialogue.visible_choices
+#[derive(Component)]
+pub struct DialogueChoiceButton(usize);
+
+pub fn setup_dialogue_overlay(
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let ui_camera = match camera_query.single() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    commands.spawn((
+        DialogueRootNode,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(30.0),
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(0.),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(theme.sizes.padding)),
+            row_gap: Val::Px(theme.sizes.gap),
+            ..default()
+        },
+        BackgroundColor(theme.colors.panel_background),
+        UiTargetCamera(ui_camera),
+        FocusScope,
+        children![
+            (
+                DialogueSpeakerText,
+                widgets::title_text(&theme, "")
+            ),
+            (
+                DialogueLineText,
+                widgets::body_text(&theme, "")
+            ),
+            (
+                DialogueChoicesNode,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Start,
+                    row_gap: Val::Px(theme.sizes.gap * 0.5),
+                    ..default()
+                }
+            )
+        ]
+    ));
+}
+
+pub fn cleanup_dialogue_overlay(
+    query: Query<Entity, With<DialogueRootNode>>,
+    mut commands: Commands
+) {
+    for root in &query {
+        commands.entity(root).despawn();
+    }
+    commands.remove_resource::<ActiveDialogue>();
+}
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+pub fn dialogue_typewriter_system(
+    mut dialogue: ResMut<ActiveDialogue>,
+    time: Res<Time>
+) {
+    if !dialogue.line_fully_revealed() {
+        dialogue.revealed_chars += time.delta_secs() * TYPEWRITER_CHARS_PER_SECOND;
+    }
+}
+
+pub fn dialogue_controls_system(
+    mut action_input: ResMut<ActionInput>,
+    mut dialogue: ResMut<ActiveDialogue>,
+    mut ingame_data: ResMut<InGameData>,
+    mut next_dialogue_state: ResMut<NextState<DialogueState>>,
+) {
+    if dialogue.showing_choices() {
+        let choice_count = dialogue.visible_choices.len();
+        if action_input.consume("Walk Forward") {
+            dialogue.selected_choice = (dialogue.selected_choice + choice_count - 1) % choice_count;
+        }
+        if action_input.consume("Walk Backward") {
+            dialogue.selected_choice = (dialogue.selected_choice + 1) % choice_count;
+        }
+    }
+
+    if !action_input.consume("Interact") {
+        return;
+    }
+
+    if !dialogue.line_fully_revealed() {
+        dialogue.revealed_chars = dialogue.current_line().chars().count() as f32;
+    } else if !dialogue.on_last_line() {
+        dialogue.line_index += 1;
+        dialogue.revealed_chars = 0.;
+    } else if dialogue.showing_choices() {
+        let selected_choice = dialogue.selected_choice;
+        choose(selected_choice, &mut dialogue, &mut ingame_data, &mut next_dialogue_state);
+    } else {
+        let next_node = first_met_branch(&dialogue.current_node().next, &ingame_data.flags).map(String::from);
+        follow(next_node, &mut dialogue, &mut ingame_data, &mut next_dialogue_state);
+    }
+}
+
+pub fn dialogue_choice_button_system(
+    interaction_query: Query<
+        (&Interaction, &DialogueChoiceButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut dialogue: ResMut<ActiveDialogue>,
+    mut ingame_data: ResMut<InGameData>,
+    mut next_dialogue_state: ResMut<NextState<DialogueState>>,
+) {
+    for (interaction, choice_button) in &interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                choose(choice_button.0, &mut dialogue, &mut ingame_data, &mut next_dialogue_state);
+                return;
+            },
+            Interaction::Hovered => {
+                dialogue.selected_choice = choice_button.0;
+            },
+            Interaction::None => {}
+        }
+    }
+}
+
+/// Keep the overlay's texts and choice buttons in step with ActiveDialogue
+pub fn update_dialogue_overlay(
+    dialogue: Res<ActiveDialogue>,
+    mut speaker_text_q: Single<&mut Text, (With<DialogueSpeakerText>, Without<DialogueLineText>)>,
+    mut line_text_q: Single<&mut Text, (With<DialogueLineText>, Without<DialogueSpeakerText>)>,
+    choices_node_q: Single<(Entity, Option<&Children>), With<DialogueChoicesNode>>,
+    choice_button_query: Query<(Entity, &DialogueChoiceButton, Has<UiSelected>)>,
+    theme: Res<UiTheme>,
+    mut commands: Commands,
+) {
+    if !dialogue.is_changed() {
+        return;
+    }
+
+    let speaker = &dialogue.current_node().speaker;
+    if speaker_text_q.0 != *speaker {
+        speaker_text_q.0 = speaker.clone();
+    }
+
+    let revealed_line: String = dialogue.current_line()
+        .chars()
+        .take(dialogue.revealed_chars as usize)
+        .collect();
+    if line_text_q.0 != revealed_line {
+        line_text_q.0 = revealed_line;
+    }
+
+    let (choices_node, choice_children) = choices_node_q.into_inner();
+    let has_choice_buttons = choice_children.is_some_and(|c| !c.is_empty());
+
+    if dialogue.showing_choices() && !has_choice_buttons {
+        let choice_texts: Vec<String> = dialogue.visible_choices
+            .iter()
+            .map(|index| dialogue.current_node().choices[*index].text.clone())
+            .collect();
+        commands.entity(choices_node).with_children(|parent| {
+            for (visible_index, text) in choice_texts.into_iter().enumerate() {
+                parent.spawn((
+                    DialogueChoiceButton(visible_index),
+                    SkipFocus,
+                    widgets::compact_button(&theme, text)
+                ));
+            }
+        });
+    } else if !dialogue.showing_choices() && has_choice_buttons {
+        commands.entity(choices_node).despawn_related::<Children>();
+    }
+
+    for (button, choice_button, marked) in &choice_button_query {
+        let selected = choice_button.0 == dialogue.selected_choice;
+        if selected && !marked {
+            commands.entity(button).insert(UiSelected);
+        } else if !selected && marked {
+            commands.entity(button).remove::<UiSelected>();
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn choose(
+    visible_index: usize,
+    dialogue: &mut ActiveDialogue,
+    ingame_data: &mut InGameData,
+    next_dialogue_state: &mut NextState<DialogueState>,
+) {
+    let choice_index = match dialogue.visible_choices.get(visible_index) {
+        Some(i) => *i,
+        None => return,
+    };
+    let choice = dialogue.current_node().choices[choice_index].clone();
+
+    ingame_data.flags.extend(choice.set_flags);
+    let next_node = first_met_branch(&choice.next, &ingame_data.flags).map(String::from);
+    follow(next_node, dialogue, ingame_data, next_dialogue_state);
+}
+
+// No next node (or a next node missing from the tree) ends the dialogue
+fn follow(
+    next_node: Option<String>,
+    dialogue: &mut ActiveDialogue,
+    ingame_data: &mut InGameData,
+    next_dialogue_state: &mut NextState<DialogueState>,
+) {
+    let entered = match next_node {
+        Some(n) => dialogue.enter_node(&n, &mut ingame_data.flags),
+        None => false,
+    };
+    if !entered {
+        next_dialogue_state.set(DialogueState::Closed);
+    }
+}
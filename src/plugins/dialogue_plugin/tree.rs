@@ -0,0 +1,82 @@
+/// Dialogue trees are read from RON files in config/dialogue.
+///
+/// A tree is a set of named nodes. Each node has a speaker and one or more lines, shown one at a
+/// time. After the last line the player picks from the node's choices (if it has any), otherwise
+/// the dialogue moves on through the node's next branches. Branches, choices and the tree's start
+/// can all be conditioned on game flags (InGameData.flags); the first branch whose condition is
+/// met is taken, and running out of branches ends the dialogue.
+use std::{ fs, collections::HashMap };
+use bevy::prelude::Reflect;
+
+use crate::reflect_ron::from_ron_str;
+
+
+#[derive(Reflect, Debug, Clone)]
+pub enum FlagCondition {
+    Set(String),
+    Unset(String),
+    Equals(String, String)
+}
+
+impl FlagCondition {
+    pub fn is_met(&self, flags: &HashMap<String, String>) -> bool {
+        match self {
+            FlagCondition::Set(key) => flags.contains_key(key),
+            FlagCondition::Unset(key) => !flags.contains_key(key),
+            FlagCondition::Equals(key, value) => flags.get(key) == Some(value),
+        }
+    }
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct DialogueBranch {
+    #[reflect(default)]
+    pub condition: Option<FlagCondition>,
+    pub node: String
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct DialogueChoice {
+    pub text: String,
+    #[reflect(default)]
+    pub condition: Option<FlagCondition>,
+    #[reflect(default)]
+    pub set_flags: HashMap<String, String>,
+    #[reflect(default)]
+    pub next: Vec<DialogueBranch>
+}
+
+// set_flags is applied as soon as the node is entered
+#[derive(Reflect, Debug, Clone)]
+pub struct DialogueNode {
+    pub speaker: String,
+    pub lines: Vec<String>,
+    #[reflect(default)]
+    pub set_flags: HashMap<String, String>,
+    #[reflect(default)]
+    pub choices: Vec<DialogueChoice>,
+    #[reflect(default)]
+    pub next: Vec<DialogueBranch>
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct DialogueTree {
+    pub start: Vec<DialogueBranch>,
+    pub nodes: HashMap<String, DialogueNode>
+}
+
+
+pub fn load_dialogue_file(filepath: &str) -> Result<DialogueTree, String> {
+    let dialogue_ron_str = fs::read_to_string(filepath).map_err(|e| format!("{}: {}", filepath, e))?;
+    from_ron_str(&dialogue_ron_str).map_err(|e| format!("{}: {}", filepath, e))
+}
+
+pub fn first_met_branch<'a>(
+    branches: &'a [DialogueBranch],
+    flags: &HashMap<String, String>
+) -> Option<&'a str> {
+    branches
+        .iter()
+        .find(|branch| branch.condition.as_ref().is_none_or(|c| c.is_met(flags)))
+        .map(|branch| branch.node.as_str())
+}
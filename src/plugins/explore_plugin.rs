@@ -2,21 +2,36 @@
 
 use std::collections::VecDeque;
 use bevy::prelude::{ 
-    App, Plugin, Update, OnExit, FixedUpdate,
-    in_state,
+    App, Plugin, Startup, PreUpdate, Update, OnExit, FixedUpdate,
+    in_state, resource_exists,
     IntoScheduleConfigs
 };
 
+pub mod collision;
+pub mod continuous;
+pub mod free_look;
+pub mod map;
 pub mod movement;
+pub mod recording;
 
 use crate::plugins::{
     explore_plugin:: {
+        continuous::{
+            continuous_movement, moving_continuously, moving_on_grid, snap_to_grid_on_exit, update_movement_mode
+        },
+        free_look::free_look,
+        map::{ MapEntered, check_tile_triggers },
         movement::{ 
-            ExplorationMovementData,
-            explore_movement_controls, execute_movement_queue, clear_movement_queue
+            ExplorationMovementData, MovementStepCompleted, QueueInput,
+            buffer_movement_input, explore_movement_controls, execute_movement_queue, clear_movement_queue
+        },
+        recording::{
+            ExplorationRecorder, ExplorationReplay, RngSeeds,
+            not_replaying, record_queue_input, replay_queue_input, setup_recording, start_recording
         },
     }, 
-    manage_state_plugin::InGameSubstate
+    exposed_config_plugin::ActionInputSystems,
+    manage_state_plugin::{ InGameSubstate, DialogueState, CharacterMenuState, PauseState }
 };
 
 
@@ -29,25 +44,88 @@ impl Plugin for ExplorePlugin {
         app.insert_resource(
             ExplorationMovementData {
                 current_movement_timer: None,
-                input_queue_buffer_timer: None,
+                input_buffer: Vec::new(),
+                held_movement: None,
+                free_looking: false,
+                moving_continuously: false,
                 current_movement_command: None,
                 command_queue: VecDeque::new(),
                 oriented_to_cardinal_directions: false,
                 cardinal_facing: None
             }
         );
+        app.add_message::<MovementStepCompleted>();
+        app.add_message::<MapEntered>();
+        app.add_message::<QueueInput>();
+        app.init_resource::<RngSeeds>();
 
+        app.add_systems(Startup, setup_recording);
+        app.add_systems(
+            PreUpdate,
+            (
+                update_movement_mode
+                    .after(ActionInputSystems)
+                    .run_if(in_state(InGameSubstate::Explore)),
+                buffer_movement_input
+                    .after(update_movement_mode)
+                    .run_if(in_state(InGameSubstate::Explore))
+                    .run_if(in_state(DialogueState::Closed))
+                    .run_if(in_state(CharacterMenuState::Closed))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(moving_on_grid)
+                    .run_if(not_replaying),
+            )
+        );
         app.add_systems(
             FixedUpdate,
             (
                 execute_movement_queue,
-                explore_movement_controls.before(execute_movement_queue),
+                explore_movement_controls
+                    .before(execute_movement_queue)
+                    .run_if(in_state(DialogueState::Closed))
+                    .run_if(in_state(CharacterMenuState::Closed)),
+                // counting the same ticks as explore_movement_controls, so a replay lines up
+                start_recording
+                    .before(explore_movement_controls)
+                    .run_if(resource_exists::<ExplorationRecorder>)
+                    .run_if(in_state(DialogueState::Closed))
+                    .run_if(in_state(CharacterMenuState::Closed)),
+                record_queue_input
+                    .after(execute_movement_queue)
+                    .run_if(resource_exists::<ExplorationRecorder>)
+                    .run_if(in_state(DialogueState::Closed))
+                    .run_if(in_state(CharacterMenuState::Closed)),
+                replay_queue_input
+                    .before(explore_movement_controls)
+                    .run_if(resource_exists::<ExplorationReplay>)
+                    .run_if(in_state(DialogueState::Closed))
+                    .run_if(in_state(CharacterMenuState::Closed)),
+            )
+            .distributive_run_if(in_state(InGameSubstate::Explore))
+            .distributive_run_if(moving_on_grid)
+        );
+        app.add_systems(
+            Update,
+            (
+                check_tile_triggers,
+                free_look
+                    .run_if(in_state(DialogueState::Closed))
+                    .run_if(in_state(CharacterMenuState::Closed))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(moving_on_grid)
+                    .run_if(not_replaying),
+                continuous_movement
+                    .before(check_tile_triggers)
+                    .run_if(in_state(DialogueState::Closed))
+                    .run_if(in_state(CharacterMenuState::Closed))
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(moving_continuously),
             )
             .distributive_run_if(in_state(InGameSubstate::Explore))
         );
 
         app.add_systems(OnExit(InGameSubstate::Explore),
-            clear_movement_queue
+            (clear_movement_queue, snap_to_grid_on_exit)
         );
 
     }
@@ -0,0 +1,36 @@
+// Which cells of the current map can be stood on
+
+use bevy::math::{ Vec2, Vec3, Vec3Swizzles };
+
+use crate::plugins::explore_plugin::map::{ MapDefinition, TileKind };
+
+/// True for a cell outside the map, or whose tile can't be walked on
+pub fn cell_is_blocked(map: &MapDefinition, cell: (i32, i32)) -> bool {
+    !matches!(map.tile_at(cell), Some(TileKind::Grass))
+}
+
+/// Where a circle of radius centred on position (world x, z) ends up once pushed out of every
+/// blocked cell it overlaps - so walking into a wall slides along it rather than stopping dead. A
+/// circle whose centre is already inside a blocked cell isn't pushed out of that cell.
+pub fn push_out_of_blocked_cells(map: &MapDefinition, position: Vec2, radius: f32) -> Vec2 {
+    let mut position = position;
+    let grid = map.grid;
+    let half_cell = Vec2::splat(grid.cell_size / 2.);
+    let (row, column) = grid.world_to_cell(Vec3::new(position.x, 0., position.y));
+    let reach = (radius / grid.cell_size).ceil() as i32;
+
+    for cell_row in row - reach..=row + reach {
+        for cell_column in column - reach..=column + reach {
+            if !cell_is_blocked(map, (cell_row, cell_column)) {
+                continue;
+            }
+            let cell_centre = grid.cell_to_world((cell_row, cell_column), 0.).xz();
+            let closest = position.clamp(cell_centre - half_cell, cell_centre + half_cell);
+            let distance = position.distance(closest);
+            if distance > 0. && distance < radius {
+                position = closest + (position - closest) / distance * radius;
+            }
+        }
+    }
+    position
+}
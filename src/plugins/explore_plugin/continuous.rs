@@ -0,0 +1,171 @@
+// Continuous movement: instead of stepping cell to cell through the movement queue, the camera
+// walks smoothly while movement bindings are held and turns with the mouse (or the turn bindings).
+// It is kept out of blocked tiles by circle-vs-tile collision (see collision.rs).
+//
+// Which mode is used comes from the current map's movement_mode, or failing that the player's
+// setting in ExposedConfig. Leaving Explore (e.g. for combat, which is played on the grid) snaps the
+// camera back onto the closest cell and cardinal direction.
+
+use bevy::{
+    input::mouse::AccumulatedMouseMotion,
+    math::{ EulerRot, Quat, Vec2, Vec3, Vec3Swizzles },
+    prelude::{ Res, ResMut, Single, With, MessageWriter, Reflect, Transform, Time, info },
+};
+
+use crate::plugins::{
+    camera_plugin::NavigateCamera,
+    explore_plugin::{
+        collision::push_out_of_blocked_cells,
+        map::{ CurrentMap, Grid, current_grid },
+        movement::{ ExplorationMovementData, MovementStepCompleted, calc_closest_cardinal_dir, cardinal_direction_angle },
+    },
+    exposed_config_plugin::{ ActionInput, ExposedConfig },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+
+// How close the camera may come to a blocked tile, in cells. Less than half a cell, so the camera
+// always stands on a cell that isn't blocked.
+pub const PLAYER_RADIUS: f32 = 0.3;
+
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MovementMode {
+    #[default]
+    Grid,
+    Continuous
+}
+
+/// The movement mode the current map asks for, or the player's setting if it doesn't
+pub fn movement_mode(exposed_config: &ExposedConfig, current_map: Option<&CurrentMap>) -> MovementMode {
+    current_map
+        .and_then(|current_map| current_map.definition.movement_mode)
+        .unwrap_or(exposed_config.movement.mode)
+}
+
+
+/////////////////////////////////////////
+// RUN CONDITIONS
+
+pub fn moving_on_grid(movement_data: Res<ExplorationMovementData>) -> bool {
+    !movement_data.moving_continuously
+}
+
+pub fn moving_continuously(movement_data: Res<ExplorationMovementData>) -> bool {
+    movement_data.moving_continuously
+}
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+/// Switch between grid and continuous movement when the map or the setting changes. Switching to
+/// continuous drops any queued grid movement where it is; switching to the grid snaps the camera
+/// onto it.
+pub fn update_movement_mode(
+    exposed_config: Res<ExposedConfig>,
+    current_map: Option<Res<CurrentMap>>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+) {
+    let continuous = movement_mode(&exposed_config, current_map.as_deref()) == MovementMode::Continuous;
+    if continuous == movement_data.moving_continuously {
+        return;
+    }
+    info!("Switching to {:?} movement", if continuous { MovementMode::Continuous } else { MovementMode::Grid });
+
+    let movement_data = movement_data.as_mut();
+    if continuous {
+        movement_data.current_movement_timer = None;
+        movement_data.current_movement_command = None;
+        movement_data.command_queue.clear();
+        movement_data.input_buffer.clear();
+        movement_data.held_movement = None;
+        movement_data.free_looking = false;
+    } else {
+        snap_to_grid(movement_data, &mut camera_transform_q.into_inner(), &current_grid(current_map.as_deref()));
+    }
+    movement_data.moving_continuously = continuous;
+}
+
+/// Walk and strafe while the movement bindings are held, turning with the mouse and the turn
+/// bindings. Crossing onto another cell writes MovementStepCompleted, as a grid step would, so tile
+/// triggers and step autosaves still happen.
+pub fn continuous_movement(
+    exposed_config: Res<ExposedConfig>,
+    action_input: Res<ActionInput>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    current_map: Option<Res<CurrentMap>>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+    mut step_completed: MessageWriter<MovementStepCompleted>,
+    time: Res<Time>,
+) {
+    let mut camera_transform = camera_transform_q.into_inner();
+    let settings = &exposed_config.movement;
+    let grid = current_grid(current_map.as_deref());
+    let axis = |positive: &str, negative: &str| {
+        action_input.held(positive) as i32 as f32 - action_input.held(negative) as i32 as f32
+    };
+
+    // x turns right, y tilts up
+    let turn = Vec2::new(mouse_motion.delta.x, -mouse_motion.delta.y) * exposed_config.free_look.mouse_sensitivity.to_radians()
+        + Vec2::new(axis("Turn Right", "Turn Left") * settings.turn_speed.to_radians() * time.delta_secs(), 0.);
+    if turn != Vec2::ZERO {
+        let (yaw, pitch, _) = camera_transform.rotation.to_euler(EulerRot::YXZ);
+        let max_pitch = exposed_config.free_look.max_pitch.to_radians();
+        camera_transform.rotation = Quat::from_euler(
+            EulerRot::YXZ,
+            yaw - turn.x,
+            (pitch + turn.y).clamp(-max_pitch, max_pitch),
+            0.
+        );
+        movement_data.oriented_to_cardinal_directions = false;
+        movement_data.cardinal_facing = None;
+    }
+
+    // (right, forward), walking level however far the camera is tilted
+    let walk = Vec2::new(axis("Strafe Right", "Strafe Left"), axis("Walk Forward", "Walk Backward"));
+    if walk == Vec2::ZERO {
+        return;
+    }
+    let heading = Quat::from_rotation_y(camera_transform.rotation.to_euler(EulerRot::YXZ).0);
+    let direction = heading * Vec3::new(walk.x, 0., -walk.y).normalize();
+    let previous_cell = grid.world_to_cell(camera_transform.translation);
+
+    let mut position = (camera_transform.translation + direction * settings.walk_speed * time.delta_secs()).xz();
+    if let Some(current_map) = current_map {
+        position = push_out_of_blocked_cells(&current_map.definition, position, PLAYER_RADIUS * grid.cell_size);
+    }
+    camera_transform.translation = Vec3::new(position.x, camera_transform.translation.y, position.y);
+
+    if grid.world_to_cell(camera_transform.translation) != previous_cell {
+        step_completed.write(MovementStepCompleted);
+    }
+}
+
+/// Leaving Explore mid-walk puts the camera back on the grid, for grid-only modes such as combat
+pub fn snap_to_grid_on_exit(
+    current_map: Option<Res<CurrentMap>>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+) {
+    if movement_data.moving_continuously {
+        snap_to_grid(&mut movement_data, &mut camera_transform_q.into_inner(), &current_grid(current_map.as_deref()));
+    }
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+// Onto the centre of the cell the camera stands on, facing the closest of the grid's directions
+fn snap_to_grid(movement_data: &mut ExplorationMovementData, camera_transform: &mut Transform, grid: &Grid) {
+    let facing = calc_closest_cardinal_dir(camera_transform, grid.directions);
+    camera_transform.translation = grid.cell_to_world(grid.world_to_cell(camera_transform.translation), camera_transform.translation.y);
+    camera_transform.rotation = Quat::from_rotation_y(cardinal_direction_angle(facing));
+    movement_data.cardinal_facing = Some(facing);
+    movement_data.oriented_to_cardinal_directions = true;
+}
@@ -0,0 +1,83 @@
+// While "Free Look" is held, the mouse or right stick turns the camera freely, off the cardinal
+// grid and tilted up or down. Letting go - or pressing a movement - turns it back to the closest of
+// the map's directions through the movement queue (see movement::enqueue_reorientation).
+
+use bevy::{
+    input::mouse::AccumulatedMouseMotion,
+    math::{ EulerRot, Quat, Vec2 },
+    prelude::{ Res, ResMut, Single, With, Query, MessageWriter, Gamepad, Transform, Time },
+};
+
+use crate::plugins::{
+    camera_plugin::NavigateCamera,
+    explore_plugin::{
+        map::{ CurrentMap, current_grid },
+        movement::{ ExplorationMovementData, QueueInput, enqueue_reorientation },
+    },
+    exposed_config_plugin::{ ActionInput, ExposedConfig },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+
+pub const FREE_LOOK_ACTION: &str = "Free Look";
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+/// Turn the camera by the mouse and right stick while FREE_LOOK_ACTION is held, clamping its pitch
+/// to FreeLookSettings.max_pitch, and marking it as no longer oriented to the cardinal directions.
+/// The camera is left alone while the movement queue turns it.
+///
+/// On release, enqueue the Face movement back to the grid - unless a movement pressed while
+/// looking has already done so - and write it as QueueInput, so it is recorded.
+pub fn free_look(
+    exposed_config: Res<ExposedConfig>,
+    action_input: Res<ActionInput>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    gamepads: Query<&Gamepad>,
+    current_map: Option<Res<CurrentMap>>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+    mut queue_input: MessageWriter<QueueInput>,
+    time: Res<Time>,
+) {
+    let movement_data = movement_data.as_mut();
+    let mut camera_transform = camera_transform_q.into_inner();
+
+    if !action_input.held(FREE_LOOK_ACTION) {
+        if movement_data.free_looking {
+            movement_data.free_looking = false;
+            if !movement_data.oriented_to_cardinal_directions {
+                let facing = enqueue_reorientation(movement_data, &camera_transform, current_grid(current_map.as_deref()).directions);
+                queue_input.write(QueueInput::Reorient(facing));
+            }
+        }
+        return;
+    }
+    movement_data.free_looking = true;
+    if !movement_data.command_queue.is_empty() {
+        return;
+    }
+
+    // x turns right, y tilts up
+    let settings = &exposed_config.free_look;
+    let stick: Vec2 = gamepads.iter().map(Gamepad::right_stick).sum();
+    let turn = Vec2::new(mouse_motion.delta.x, -mouse_motion.delta.y) * settings.mouse_sensitivity.to_radians()
+        + stick * settings.stick_speed.to_radians() * time.delta_secs();
+    if turn == Vec2::ZERO {
+        return;
+    }
+
+    let (yaw, pitch, _) = camera_transform.rotation.to_euler(EulerRot::YXZ);
+    let max_pitch = settings.max_pitch.to_radians();
+    camera_transform.rotation = Quat::from_euler(
+        EulerRot::YXZ,
+        yaw - turn.x,
+        (pitch + turn.y).clamp(-max_pitch, max_pitch),
+        0.
+    );
+    movement_data.oriented_to_cardinal_directions = false;
+}
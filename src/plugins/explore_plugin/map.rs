@@ -0,0 +1,223 @@
+/// This file defines the map data read from config/maps, and the resource holding whichever map
+/// the player is currently exploring.
+///
+/// Cells are addressed (row, column), the same way the tiles strings are laid out. Row 0 is the
+/// northernmost row; a cell's centre sits at x = column * cell_size, z = row * cell_size, where
+/// cell_size is set by the map's grid (see Grid).
+///
+/// Resources in this file: CurrentMap
+/// Messages in this file: MapEntered
+use std::{ fs, collections::HashMap };
+use bevy::prelude::{
+    Component, Resource, Message, MessageReader, MessageWriter, Reflect, Res, ResMut, NextState,
+    Commands, Single, With, Transform, Vec3
+};
+
+use crate::{
+    reflect_ron::from_ron_str,
+    plugins::{
+        camera_plugin::NavigateCamera,
+        dialogue_plugin::StartDialogue,
+        explore_plugin::{
+            continuous::MovementMode,
+            movement::{ DirectionSet, MovementStepCompleted },
+        },
+        manage_state_plugin::{
+            InGameSubstate,
+            ingame_state_plugin::shop_substate::ShopVisit,
+        },
+    },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+pub const DEFAULT_MAP_FILEPATH: &str = "config/maps/test.ron";
+pub const MAPS_DIRPATH: &str = "config/maps";
+// The distance between cell centres on a map that doesn't set its own
+pub const DEFAULT_CELL_SIZE: f32 = 5.0;
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileKind {
+    Inaccessible,
+    Grass
+}
+
+// What happens when the player finishes a step onto a cell
+#[derive(Reflect, Debug, Clone)]
+pub enum TileTrigger {
+    Dialogue(String),
+    Shop(ShopDefinition)
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct ShopDefinition {
+    pub name: String,
+    #[reflect(default)]
+    pub greeting_dialogue: Option<String>
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct MapTrigger {
+    pub cell: (i32, i32),
+    pub trigger: TileTrigger
+}
+
+// Standing on the cell in front of an NPC and pressing "Interact" starts its dialogue
+#[derive(Reflect, Debug, Clone)]
+pub struct MapNpc {
+    pub name: String,
+    pub cell: (i32, i32),
+    pub dialogue: String
+}
+
+// size is (rows, columns). Each string in tiles is one row, and each character in a row is looked
+// up in key to find what kind of tile it is.
+// movement_mode is only given for maps that need one (e.g. Continuous for outdoor areas); other maps
+// use the player's setting.
+#[derive(Reflect, Debug, Clone)]
+pub struct MapDefinition {
+    pub name: String,
+    pub size: (u32, u32),
+    pub tiles: Vec<String>,
+    pub key: HashMap<String, TileKind>,
+    #[reflect(default)]
+    pub grid: Grid,
+    #[reflect(default)]
+    pub triggers: Vec<MapTrigger>,
+    #[reflect(default)]
+    pub npcs: Vec<MapNpc>,
+    #[reflect(default)]
+    pub movement_mode: Option<MovementMode>
+}
+
+// How far apart a map's cell centres are, and which directions can be faced on it: e.g.
+// grid: (cell_size: 3.0, directions: EightWay). Either can be left out for the default
+// (DEFAULT_CELL_SIZE, FourWay).
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub struct Grid {
+    #[reflect(default = "default_cell_size")]
+    pub cell_size: f32,
+    #[reflect(default)]
+    pub directions: DirectionSet
+}
+
+fn default_cell_size() -> f32 {
+    DEFAULT_CELL_SIZE
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Grid {
+            cell_size: DEFAULT_CELL_SIZE,
+            directions: DirectionSet::FourWay
+        }
+    }
+}
+
+impl Grid {
+    pub fn world_to_cell(&self, translation: Vec3) -> (i32, i32) {
+        (
+            (translation.z / self.cell_size).round() as i32,
+            (translation.x / self.cell_size).round() as i32
+        )
+    }
+
+    pub fn cell_to_world(&self, cell: (i32, i32), y: f32) -> Vec3 {
+        Vec3::new(cell.1 as f32 * self.cell_size, y, cell.0 as f32 * self.cell_size)
+    }
+}
+
+/// The grid of the map being explored, or the default grid when there is none
+pub fn current_grid(current_map: Option<&CurrentMap>) -> Grid {
+    current_map.map_or(Grid::default(), |current_map| current_map.definition.grid)
+}
+
+#[derive(Resource, Debug)]
+pub struct CurrentMap {
+    pub filepath: String,
+    pub definition: MapDefinition
+}
+
+/// Marks a spawned NPC. Facing its cell and pressing "Interact" starts its dialogue.
+#[derive(Component, Debug)]
+pub struct Npc {
+    pub name: String,
+    pub cell: (i32, i32),
+    pub dialogue: String
+}
+
+/// Written whenever the player arrives on a map by playing (starting a new game, travelling) -
+/// but not when a map is restored by loading a save.
+#[derive(Message, Debug)]
+pub struct MapEntered {
+    pub filepath: String
+}
+
+
+pub fn load_map_file(filepath: &str) -> Result<CurrentMap, String> {
+    let map_ron_str = fs::read_to_string(filepath).map_err(|e| format!("{}: {}", filepath, e))?;
+    let definition: MapDefinition = from_ron_str(&map_ron_str).map_err(|e| format!("{}: {}", filepath, e))?;
+    Ok(CurrentMap {
+        filepath: String::from(filepath),
+        definition
+    })
+}
+
+impl MapDefinition {
+    /// None for a cell outside the map, or a character missing from key
+    pub fn tile_at(&self, cell: (i32, i32)) -> Option<TileKind> {
+        let row = self.tiles.get(usize::try_from(cell.0).ok()?)?;
+        let tile = row.chars().nth(usize::try_from(cell.1).ok()?)?;
+        self.key.get(&tile.to_string()).copied()
+    }
+}
+
+/// The map files in MAPS_DIRPATH, in alphabetical order
+pub fn list_map_files() -> Vec<String> {
+    let entries = match fs::read_dir(MAPS_DIRPATH) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+    let mut map_files: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            match path.extension()?.to_str()? {
+                "ron" => Some(format!("{}/{}", MAPS_DIRPATH, path.file_name()?.to_str()?)),
+                _ => None,
+            }
+        })
+        .collect();
+    map_files.sort();
+    map_files
+}
+
+/// After each completed step, fire the trigger on the cell the player is now standing on (if any)
+pub fn check_tile_triggers(
+    mut steps_completed: MessageReader<MovementStepCompleted>,
+    current_map: Res<CurrentMap>,
+    camera_transform_q: Single<&Transform, With<NavigateCamera>>,
+    mut commands: Commands,
+    mut start_dialogue: MessageWriter<StartDialogue>,
+    mut next_substate: ResMut<NextState<InGameSubstate>>,
+) {
+    if steps_completed.read().count() == 0 {
+        return;
+    }
+
+    let cell = current_map.definition.grid.world_to_cell(camera_transform_q.into_inner().translation);
+    let map_trigger = current_map.definition.triggers.iter().find(|t| t.cell == cell);
+    match map_trigger.map(|t| &t.trigger) {
+        Some(TileTrigger::Dialogue(dialogue_filepath)) => {
+            start_dialogue.write(StartDialogue { filepath: dialogue_filepath.clone() });
+        },
+        Some(TileTrigger::Shop(shop)) => {
+            commands.insert_resource(ShopVisit {
+                name: shop.name.clone(),
+                greeting_dialogue: shop.greeting_dialogue.clone()
+            });
+            next_substate.set(InGameSubstate::Shop);
+        },
+        None => {}
+    }
+}
@@ -2,56 +2,159 @@
 /// sequentially by queue and exposing API to enqueue commands.
 ///
 /// Resources in this plugin: ExplorationMovementData, ExplorationLocationData
+/// Messages in this plugin: MovementStepCompleted, QueueInput
 ///
 /// Systems in this plugin are called in ExplorePlugin (src/plugins/explore_plugin)
 use std::collections::VecDeque;
-use std::f32::consts::{ PI, FRAC_PI_2 };
+use std::f32::consts::{ PI, FRAC_PI_2, FRAC_PI_4, SQRT_2 };
 use bevy::prelude::{
-    Resource, Res, ResMut, Single, With, Query, Name,
-    ButtonInput, KeyCode, Gamepad, GamepadButton,
-    Transform, Dir3, Time, Timer, TimerMode, 
+    Resource, Res, ResMut, Single, With, Message, MessageWriter, Reflect,
+    Transform, Time, Timer, TimerMode, 
     info
 };
-use bevy::math::{ Quat, Vec3 };
+use bevy::math::{ EulerRot, Quat, Vec3 };
 
 use crate::plugins::{
     camera_plugin::NavigateCamera,
-    exposed_config_plugin::ExposedConfig
+    explore_plugin::{ collision::cell_is_blocked, map::{ CurrentMap, Grid, current_grid } },
+    exposed_config_plugin::{ ActionInput, ExposedConfig }
 };
 
 
 /////////////////////////////////////////
 // CONFIGURABLES
+// Seconds a step to a neighbouring cell (or a quarter turn) takes. A diagonal step takes as much
+// longer as it is further; turning around takes TURN_AROUND_DURATION.
 const MOVESTEP_DURATION: f32 = 0.3;
-const INPUT_BUFFER: f32 = 0.08;
+const TURN_AROUND_DURATION: f32 = 0.45;
 
-// Temporarily a constant here
-// in the future, let user determine grid size (as well as cardinal direction angles)
-const MOVESTEP_DISTANCE: f32 = 5.0;
-const CARDINAL_DIRECTION_ANGLES: [f32; 4] = [0., -FRAC_PI_2, PI, FRAC_PI_2];
+// The camera's rotation about y facing each CardinalDirection, in the same (clockwise) order
+const CARDINAL_DIRECTION_ANGLES: [f32; 8] = [
+    0., -FRAC_PI_4, -FRAC_PI_2, -3. * FRAC_PI_4, PI, 3. * FRAC_PI_4, FRAC_PI_2, FRAC_PI_4
+];
 
-// For the movements beginning with 'Face', users will never directly input them - they are
-// reserved for reorienting to cardinal direction grid
-#[derive(Debug)]
+// Users will never directly input Face - it is reserved for reorienting to the map's directions
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExplorationMovements {
     WalkForward,
     WalkBackward,
     StrafeRight,
     StrafeLeft,
+    // diagonal steps, keeping the same facing - only on maps with DirectionSet::EightWay
+    StrafeForwardRight,
+    StrafeForwardLeft,
+    StrafeBackRight,
+    StrafeBackLeft,
     TurnClockw,
     TurnCounterclockw,
-    FaceNorth,
-    FaceEast,
-    FaceSouth,
-    FaceWest
+    TurnAround,
+    Face(CardinalDirection)
 }
 
-#[derive(Debug)]
+impl ExplorationMovements {
+    /// For a step, eighths of a turn clockwise from the facing that it moves in
+    fn step_rotation(self) -> Option<i32> {
+        match self {
+            ExplorationMovements::WalkForward => Some(0),
+            ExplorationMovements::StrafeForwardRight => Some(1),
+            ExplorationMovements::StrafeRight => Some(2),
+            ExplorationMovements::StrafeBackRight => Some(3),
+            ExplorationMovements::WalkBackward => Some(4),
+            ExplorationMovements::StrafeBackLeft => Some(-3),
+            ExplorationMovements::StrafeLeft => Some(-2),
+            ExplorationMovements::StrafeForwardLeft => Some(-1),
+            _ => None
+        }
+    }
+
+    fn is_diagonal_strafe(self) -> bool {
+        matches!(self.step_rotation(), Some(eighths) if eighths % 2 != 0)
+    }
+}
+
+// The exploration_controls action each movement is bound to in ExposedConfig
+const MOVEMENT_ACTIONS: [(&str, ExplorationMovements); 11] = [
+    ("Walk Forward", ExplorationMovements::WalkForward),
+    ("Walk Backward", ExplorationMovements::WalkBackward),
+    ("Strafe Left", ExplorationMovements::StrafeLeft),
+    ("Strafe Right", ExplorationMovements::StrafeRight),
+    ("Strafe Forward Left", ExplorationMovements::StrafeForwardLeft),
+    ("Strafe Forward Right", ExplorationMovements::StrafeForwardRight),
+    ("Strafe Back Left", ExplorationMovements::StrafeBackLeft),
+    ("Strafe Back Right", ExplorationMovements::StrafeBackRight),
+    ("Turn Left", ExplorationMovements::TurnCounterclockw),
+    ("Turn Right", ExplorationMovements::TurnClockw),
+    ("Turn Around", ExplorationMovements::TurnAround),
+];
+const CANCEL_MOVES_ACTION: &str = "Cancel Moves";
+
+// Clockwise from North. The diagonals can only be faced on maps with DirectionSet::EightWay.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CardinalDirection {
-    North,  // 0
-    East,   // 1
-    South,  // 2
-    West    // 3
+    North,      // 0
+    NorthEast,  // 1
+    East,       // 2
+    SouthEast,  // 3
+    South,      // 4
+    SouthWest,  // 5
+    West,       // 6
+    NorthWest   // 7
+}
+
+const CARDINAL_DIRECTIONS: [CardinalDirection; 8] = [
+    CardinalDirection::North, CardinalDirection::NorthEast, CardinalDirection::East, CardinalDirection::SouthEast,
+    CardinalDirection::South, CardinalDirection::SouthWest, CardinalDirection::West, CardinalDirection::NorthWest
+];
+
+impl CardinalDirection {
+    /// The direction eighths eighths of a turn clockwise (negative for counterclockwise) from this
+    pub fn rotated(self, eighths: i32) -> CardinalDirection {
+        CARDINAL_DIRECTIONS[(self as i32 + eighths).rem_euclid(8) as usize]
+    }
+
+    /// (row, column) offset to the neighbouring cell in this direction
+    pub fn cell_offset(self) -> (i32, i32) {
+        match self {
+            CardinalDirection::North => (-1, 0),
+            CardinalDirection::NorthEast => (-1, 1),
+            CardinalDirection::East => (0, 1),
+            CardinalDirection::SouthEast => (1, 1),
+            CardinalDirection::South => (1, 0),
+            CardinalDirection::SouthWest => (1, -1),
+            CardinalDirection::West => (0, -1),
+            CardinalDirection::NorthWest => (-1, -1),
+        }
+    }
+}
+
+/// The directions the player can face on a map (see map::Grid): the four cardinal directions, or
+/// those and the diagonals between them
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirectionSet {
+    #[default]
+    FourWay,
+    EightWay
+}
+
+impl DirectionSet {
+    pub fn directions(self) -> &'static [CardinalDirection] {
+        match self {
+            DirectionSet::FourWay => &[
+                CardinalDirection::North, CardinalDirection::East, CardinalDirection::South, CardinalDirection::West
+            ],
+            DirectionSet::EightWay => &CARDINAL_DIRECTIONS,
+        }
+    }
+
+    // eighths of a turn between neighbouring directions of the set
+    fn step(self) -> i32 {
+        8 / self.directions().len() as i32
+    }
+}
+
+/// The camera's rotation about y when facing direction
+pub fn cardinal_direction_angle(direction: CardinalDirection) -> f32 {
+    CARDINAL_DIRECTION_ANGLES[direction as usize]
 }
 
 pub enum MovementType {
@@ -59,27 +162,26 @@ pub enum MovementType {
     Translation
 }
 
-fn cardinal_direction_rot_clockwise(current_cardinal_facing: &CardinalDirection) -> (CardinalDirection, f32) {
-    match current_cardinal_facing {
-        CardinalDirection::North => (CardinalDirection::East, CARDINAL_DIRECTION_ANGLES[1]),
-        CardinalDirection::East => (CardinalDirection::South, CARDINAL_DIRECTION_ANGLES[2]),
-        CardinalDirection::South => (CardinalDirection::West, CARDINAL_DIRECTION_ANGLES[3]),
-        CardinalDirection::West => (CardinalDirection::North, CARDINAL_DIRECTION_ANGLES[0]),
-    }
+/// The next direction of directions clockwise from current_cardinal_facing, and its angle. A facing
+/// that isn't part of directions (a diagonal on a FourWay map) turns to the closest one clockwise.
+pub fn cardinal_direction_rot_clockwise(current_cardinal_facing: &CardinalDirection, directions: DirectionSet) -> (CardinalDirection, f32) {
+    let step = directions.step();
+    let next = CardinalDirection::North.rotated((*current_cardinal_facing as i32 / step + 1) * step);
+    (next, cardinal_direction_angle(next))
 }
 
-fn cardinal_direction_rot_counterclockwise(current_cardinal_facing: &CardinalDirection) -> (CardinalDirection, f32) {
-    match current_cardinal_facing {
-        CardinalDirection::North => (CardinalDirection::West, CARDINAL_DIRECTION_ANGLES[3]),
-        CardinalDirection::East => (CardinalDirection::North, CARDINAL_DIRECTION_ANGLES[0]),
-        CardinalDirection::South => (CardinalDirection::East, CARDINAL_DIRECTION_ANGLES[1]),
-        CardinalDirection::West => (CardinalDirection::South, CARDINAL_DIRECTION_ANGLES[2]),
-    }
+/// As cardinal_direction_rot_clockwise, but counterclockwise
+pub fn cardinal_direction_rot_counterclockwise(current_cardinal_facing: &CardinalDirection, directions: DirectionSet) -> (CardinalDirection, f32) {
+    let step = directions.step();
+    let next = CardinalDirection::North.rotated(((*current_cardinal_facing as i32 + step - 1) / step - 1) * step);
+    (next, cardinal_direction_angle(next))
 }
 
 
 pub struct CurrentMovementCommand {
     pub movement_type: MovementType,
+    // seconds the movement takes
+    pub duration: f32,
     pub beginning_translation: Option<Vec3>,
     pub end_translation: Option<Vec3>,
     pub beginning_rotation: Option<Quat>,
@@ -89,158 +191,255 @@ pub struct CurrentMovementCommand {
 #[derive(Resource)]
 pub struct ExplorationMovementData {
     pub current_movement_timer: Option<Timer>,
-    pub input_queue_buffer_timer: Option<Timer>,
+    // moves pressed since FixedUpdate last ran, waiting to be enqueued
+    pub input_buffer: Vec<ExplorationMovements>,
+    pub held_movement: Option<HeldMovement>,
+    // "Free Look" is held (see free_look)
+    pub free_looking: bool,
+    // the camera walks freely instead of stepping through command_queue (see continuous)
+    pub moving_continuously: bool,
     pub current_movement_command: Option<CurrentMovementCommand>,
     pub command_queue: VecDeque<ExplorationMovements>,
     pub oriented_to_cardinal_directions: bool,
     pub cardinal_facing: Option<CardinalDirection>,
 }
 
-// currently even though ExposedConfig holds gamepad settings, we are only processing keyboard
-// inputs
-pub fn explore_movement_controls(
+// The movement whose binding was pressed last and is still held down. It is repeated once
+// repeat_timer finishes and nothing else is queued.
+#[derive(Debug)]
+pub struct HeldMovement {
+    pub movement: ExplorationMovements,
+    pub repeat_timer: Timer
+}
+
+/// Written each time a translation (a step from one grid cell to the next) finishes executing
+#[derive(Message, Debug)]
+pub struct MovementStepCompleted;
+
+/// Written each time the player's input changes the movement queue - a move enqueued by
+/// explore_movement_controls, a turn back to the grid after free-look, or "Cancel Moves" - so that
+/// recording.rs can record it
+#[derive(Reflect, Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueInput {
+    Move(ExplorationMovements),
+    Reorient(CardinalDirection),
+    CancelMoves
+}
+
+/// Moves each press of a movement action (keyboard or gamepad, see ActionInput) into input_buffer.
+/// Runs in PreUpdate, every frame - FixedUpdate can run any number of times in a frame, including
+/// none, so reading presses there would see one twice or miss it.
+///
+/// "Cancel Moves" drops everything queued behind the move being made, including presses not yet
+/// enqueued.
+///
+/// A movement binding held down repeats its move (see MovementSettings) each time the step before
+/// it finishes, until it is released or a step is blocked (see execute_movement_queue).
+pub fn buffer_movement_input(
     exposed_config: Res<ExposedConfig>,
-    // controller_input: Query<(&Name, &Gamepad)>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut action_input: ResMut<ActionInput>,
     mut movement_data: ResMut<ExplorationMovementData>,
-    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
-    time: Res<Time>
+    mut queue_input: MessageWriter<QueueInput>,
+    time: Res<Time>,
 ) {
-    // if between_inputs timer exists and is not finished - tick and return early
-    // else - do not enqueue input
-    if movement_data.input_queue_buffer_timer.is_some() {
-        let timer = movement_data.input_queue_buffer_timer.as_mut().unwrap();
-        if !timer.is_finished() {
-            timer.tick(time.delta());
-            return;
-        } else {
-            // between_inputs timer is finished:
-            // reset between_inputs in movementdata to none so in cases where movement commands were not just
-            // pressed, we can skip the above checks
-            movement_data.input_queue_buffer_timer = None;
+    let movement_data = movement_data.as_mut();
+
+    if action_input.consume(CANCEL_MOVES_ACTION) {
+        cancel_queued_movements(movement_data);
+        queue_input.write(QueueInput::CancelMoves);
+    }
+    for (action, movement) in MOVEMENT_ACTIONS {
+        if action_input.consume(action) {
+            movement_data.input_buffer.push(movement);
+            movement_data.held_movement = Some(HeldMovement {
+                movement,
+                repeat_timer: Timer::from_seconds(exposed_config.movement.repeat_delay, TimerMode::Once)
+            });
         }
     }
 
-    let k_bindings = &exposed_config.keyboard_bindings.exploration_controls;
-
-    if keyboard_input.just_pressed(k_bindings["Walk Forward"]) {
-        // if we are not oriented to cardinal directions - reorient.
-        enqueue_movement(ExplorationMovements::WalkForward, movement_data, camera_transform_q);
-    } 
-    else if keyboard_input.just_pressed(k_bindings["Walk Backward"]) {
-        enqueue_movement(ExplorationMovements::WalkBackward, movement_data, camera_transform_q);
-    }
-    else if keyboard_input.just_pressed(k_bindings["Strafe Left"]) {
-        enqueue_movement(ExplorationMovements::StrafeLeft, movement_data, camera_transform_q);
+    let held = match movement_data.held_movement.as_mut() {
+        Some(h) => h,
+        None => return,
+    };
+    if !action_input.held(movement_action(held.movement)) {
+        movement_data.held_movement = None;
+        return;
     }
-    else if keyboard_input.just_pressed(k_bindings["Strafe Right"]) {
-        enqueue_movement(ExplorationMovements::StrafeRight, movement_data, camera_transform_q);
+    held.repeat_timer.tick(time.delta());
+    let waiting_on_step = !movement_data.input_buffer.is_empty() || !movement_data.command_queue.is_empty();
+    if !held.repeat_timer.is_finished() || waiting_on_step {
+        return;
     }
-    else if keyboard_input.just_pressed(k_bindings["Turn Left"]) {
-        enqueue_movement(ExplorationMovements::TurnCounterclockw, movement_data, camera_transform_q);
+
+    movement_data.input_buffer.push(held.movement);
+    held.repeat_timer = Timer::from_seconds(exposed_config.movement.repeat_interval, TimerMode::Once);
+}
+
+/// Enqueues the moves buffered since the last FixedUpdate, so each press is enqueued exactly once.
+/// Moves beyond the configured max_queued_moves are dropped.
+pub fn explore_movement_controls(
+    exposed_config: Res<ExposedConfig>,
+    current_map: Option<Res<CurrentMap>>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    camera_transform_q: Single<&Transform, With<NavigateCamera>>,
+    mut queue_input: MessageWriter<QueueInput>,
+) {
+    if movement_data.input_buffer.is_empty() {
+        return;
     }
-    else if keyboard_input.just_pressed(k_bindings["Turn Right"]) {
-        enqueue_movement(ExplorationMovements::TurnClockw, movement_data, camera_transform_q);
+    let camera_transform = camera_transform_q.into_inner();
+    let directions = current_grid(current_map.as_deref()).directions;
+    let max_queued_moves = exposed_config.movement.max_queued_moves as usize;
+
+    for movement in std::mem::take(&mut movement_data.input_buffer) {
+        if movement.is_diagonal_strafe() && directions == DirectionSet::FourWay {
+            info!("Dropping {:?}, diagonal steps need an eight-way grid", movement);
+            continue;
+        }
+        // the front of the queue is the move being made - or, while idle, the one about to start this
+        // tick - so it isn't waiting; into an empty queue a move is always let in, as the front
+        let waiting = movement_data.command_queue.len().saturating_sub(1);
+        if !movement_data.command_queue.is_empty() && waiting >= max_queued_moves {
+            info!("Dropping {:?}, {} moves already queued", movement, waiting);
+            continue;
+        }
+        // reoriented here rather than by enqueue_movement, so that the turn is recorded too
+        if !movement_data.oriented_to_cardinal_directions {
+            let facing = enqueue_reorientation(&mut movement_data, camera_transform, directions);
+            queue_input.write(QueueInput::Reorient(facing));
+        }
+        enqueue_movement(movement, &mut movement_data, camera_transform, directions);
+        queue_input.write(QueueInput::Move(movement));
     }
 }
 
 
-fn calc_closest_cardinal_dir(
-    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+// The exploration_controls action movement is bound to
+fn movement_action(movement: ExplorationMovements) -> &'static str {
+    MOVEMENT_ACTIONS
+        .iter()
+        .find(|(_, bound_movement)| *bound_movement == movement)
+        .map_or("", |(action, _)| *action)
+}
+
+
+/// The direction of directions the camera's heading is closest to
+pub fn calc_closest_cardinal_dir(
+    camera_transform: &Transform,
+    directions: DirectionSet,
 ) 
     -> CardinalDirection 
 {
-    let cardinal_dirs = CARDINAL_DIRECTION_ANGLES.map(Quat::from_rotation_y);
-    let camera_transform_rot = camera_transform_q.into_inner().rotation;
-
-    let rot_dist_to_cardinal_dirs: [f32; 4] = cardinal_dirs.map(|a| a.angle_between(camera_transform_rot));
-    let mut closest_cardinal_dir_index = 0;
+    // only the heading counts, not how far free-look has tilted the camera up or down
+    let camera_transform_rot = Quat::from_rotation_y(camera_transform.rotation.to_euler(EulerRot::YXZ).0);
+    let rot_dist = |direction: &CardinalDirection| {
+        Quat::from_rotation_y(cardinal_direction_angle(*direction)).angle_between(camera_transform_rot)
+    };
 
-    for (index, this_rot_dist) in rot_dist_to_cardinal_dirs.iter().enumerate().skip(1) {
-        if *this_rot_dist < rot_dist_to_cardinal_dirs[closest_cardinal_dir_index] {
-            closest_cardinal_dir_index = index;
+    let mut closest_cardinal_dir = directions.directions()[0];
+    for direction in directions.directions().iter().skip(1) {
+        if rot_dist(direction) < rot_dist(&closest_cardinal_dir) {
+            closest_cardinal_dir = *direction;
         }
     }
-    match closest_cardinal_dir_index {
-        0 => CardinalDirection::North,
-        1 => CardinalDirection::East,
-        2 => CardinalDirection::South,
-        3 => CardinalDirection::West,
-        _ => CardinalDirection::North
-    }
+    closest_cardinal_dir
 }
 
 
+/// Where the camera will be once dequeued_movement has been executed from cam_transform, on grid
 fn contextualize_current_movement(
-    dequeued_movement: ExplorationMovements,
+    dequeued_movement: &ExplorationMovements,
     cam_transform: &Transform,
-    cardinal_facing: Option<CardinalDirection>
+    cardinal_facing: Option<CardinalDirection>,
+    grid: &Grid
 ) -> CurrentMovementCommand {
     let (beginning_rotation, end_rotation): (Option<Quat>, Option<Quat>);
     let (beginning_translation, end_translation): (Option<Vec3>, Option<Vec3>);
 
-    let movement_type = match &dequeued_movement {
-        ExplorationMovements::WalkForward | ExplorationMovements::WalkBackward | ExplorationMovements::StrafeLeft | ExplorationMovements::StrafeRight => MovementType::Translation,
-        _ => MovementType::Rotation
+    let movement_type = match dequeued_movement.step_rotation() {
+        Some(_) => MovementType::Translation,
+        None => MovementType::Rotation
     };
+    let duration;
 
-    if let &MovementType::Translation = &movement_type {
+    if let Some(step_rotation) = dequeued_movement.step_rotation() {
         (beginning_rotation, end_rotation) = (None, None);
         beginning_translation = Some(cam_transform.translation);
-        end_translation = Some(MOVESTEP_DISTANCE * match &dequeued_movement {
-            ExplorationMovements::WalkForward => -cam_transform.local_z(),
-            ExplorationMovements::WalkBackward => cam_transform.local_z(),
-            ExplorationMovements::StrafeLeft => -cam_transform.local_x(),
-            ExplorationMovements::StrafeRight => cam_transform.local_x(),
-            _ => -cam_transform.local_z()
-        });
+        // a move is always made facing one of the grid's directions (see enqueue_movement)
+        let facing = cardinal_facing.unwrap_or_else(|| calc_closest_cardinal_dir(cam_transform, grid.directions));
+        let (row_offset, column_offset) = facing.rotated(step_rotation).cell_offset();
+        end_translation = Some(cam_transform.translation + grid.cell_size * Vec3::new(column_offset as f32, 0., row_offset as f32));
+        duration = if row_offset != 0 && column_offset != 0 { MOVESTEP_DURATION * SQRT_2 } else { MOVESTEP_DURATION };
     } else {
         (beginning_translation, end_translation) = (None, None);
         beginning_rotation = Some(cam_transform.rotation);
-        end_rotation = match &dequeued_movement {
-            ExplorationMovements::FaceNorth => Some(Quat::from_rotation_y(CARDINAL_DIRECTION_ANGLES[0])),
-            ExplorationMovements::FaceEast  => Some(Quat::from_rotation_y(CARDINAL_DIRECTION_ANGLES[1])),
-            ExplorationMovements::FaceSouth => Some(Quat::from_rotation_y(CARDINAL_DIRECTION_ANGLES[2])),
-            ExplorationMovements::FaceWest  => Some(Quat::from_rotation_y(CARDINAL_DIRECTION_ANGLES[3])),
-            // BELOW WILL ONLY EVER BE EXECUTED WHILE USER IS ALREADY ORIENTED TO CARDINAL
-            // DIRECTIONS
-            ExplorationMovements::TurnClockw => Some(Quat::from_rotation_y(cardinal_direction_rot_clockwise(&cardinal_facing.unwrap()).1)),
-            ExplorationMovements::TurnCounterclockw => Some(Quat::from_rotation_y(cardinal_direction_rot_counterclockwise(&cardinal_facing.unwrap()).1)),
-            _ => Some(Quat::from_rotation_y(CARDINAL_DIRECTION_ANGLES[0])),
+        end_rotation = facing_after(dequeued_movement, cardinal_facing, grid.directions)
+            .map(|facing| Quat::from_rotation_y(cardinal_direction_angle(facing)));
+        duration = match dequeued_movement {
+            ExplorationMovements::TurnAround => TURN_AROUND_DURATION,
+            _ => MOVESTEP_DURATION
         };
     }
     CurrentMovementCommand {
         movement_type,
+        duration,
         beginning_translation, end_translation,
         beginning_rotation, end_rotation
     }
 }
 
+/// The cardinal direction faced once movement has been executed
+fn facing_after(
+    movement: &ExplorationMovements,
+    cardinal_facing: Option<CardinalDirection>,
+    directions: DirectionSet
+) -> Option<CardinalDirection> {
+    match movement {
+        // BELOW WILL ONLY EVER BE EXECUTED WHILE USER IS ALREADY ORIENTED TO CARDINAL DIRECTIONS
+        ExplorationMovements::TurnClockw => cardinal_facing.map(|facing| cardinal_direction_rot_clockwise(&facing, directions).0),
+        ExplorationMovements::TurnCounterclockw => cardinal_facing.map(|facing| cardinal_direction_rot_counterclockwise(&facing, directions).0),
+        ExplorationMovements::TurnAround => cardinal_facing.map(|facing| facing.rotated(4)),
+        ExplorationMovements::Face(direction) => Some(*direction),
+        _ => cardinal_facing
+    }
+}
+
 
-/// This system should be called when a user has just pressed a button warranting a movement
-/// Set up buffer to prevent multiple inputs from being registered from single button input
+/// This should be called when a user has pressed a button warranting a movement
 pub fn enqueue_movement(
     movement: ExplorationMovements,
-    mut movement_data: ResMut<ExplorationMovementData>,
-    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+    movement_data: &mut ExplorationMovementData,
+    camera_transform: &Transform,
+    directions: DirectionSet,
 ) {
     // if a movement is enqueued but we are not in cardinal - inject new movement to reorient
     // to cardinal, then enqueue the movement
     if !movement_data.oriented_to_cardinal_directions {
-        let closest_cardinal = calc_closest_cardinal_dir(camera_transform_q);
-        match closest_cardinal {
-            CardinalDirection::North => { movement_data.command_queue.push_back(ExplorationMovements::FaceNorth); },
-            CardinalDirection::East => { movement_data.command_queue.push_back(ExplorationMovements::FaceEast); },
-            CardinalDirection::South => { movement_data.command_queue.push_back(ExplorationMovements::FaceSouth); },
-            CardinalDirection::West => { movement_data.command_queue.push_back(ExplorationMovements::FaceWest); },
-        }
-        info!("Enqueueing {:?}", closest_cardinal);
-        movement_data.oriented_to_cardinal_directions = true;
+        enqueue_reorientation(movement_data, camera_transform, directions);
     }
 
     info!("Enqueueing {:?}", movement);
     movement_data.command_queue.push_back(movement);
-    movement_data.input_queue_buffer_timer = Some(Timer::from_seconds(INPUT_BUFFER, TimerMode::Once));
+}
+
+/// Enqueue the Face movement turning the camera to the direction of directions closest to where
+/// it points, e.g. after free-look, returning that direction
+pub fn enqueue_reorientation(
+    movement_data: &mut ExplorationMovementData,
+    camera_transform: &Transform,
+    directions: DirectionSet,
+) -> CardinalDirection {
+    let closest_cardinal = calc_closest_cardinal_dir(camera_transform, directions);
+    enqueue_facing(movement_data, closest_cardinal);
+    closest_cardinal
+}
+
+/// Enqueue the Face movement turning the camera to facing, after which it is oriented to the grid
+pub fn enqueue_facing(movement_data: &mut ExplorationMovementData, facing: CardinalDirection) {
+    movement_data.command_queue.push_back(ExplorationMovements::Face(facing));
+    info!("Enqueueing {:?}", facing);
+    movement_data.oriented_to_cardinal_directions = true;
 }
 
 
@@ -248,143 +447,99 @@ pub fn enqueue_movement(
 /// clear
 pub fn clear_movement_queue(mut movement_data: ResMut<ExplorationMovementData>) {
     movement_data.current_movement_timer = None;
+    movement_data.current_movement_command = None;
     movement_data.command_queue.clear();
+    movement_data.input_buffer.clear();
+    movement_data.held_movement = None;
+}
+
+/// Drop everything queued behind the movement currently executing (if any), so that it finishes
+/// but nothing further starts - e.g. when a dialogue opens mid-walk
+pub fn cancel_queued_movements(movement_data: &mut ExplorationMovementData) {
+    let keep = movement_data.current_movement_command.is_some() as usize;
+    let dropped_reorientation = movement_data.command_queue
+        .iter()
+        .skip(keep)
+        .any(|movement| matches!(movement, ExplorationMovements::Face(_)));
+    movement_data.command_queue.truncate(keep);
+    movement_data.input_buffer.clear();
+    movement_data.held_movement = None;
+    // the camera never turned to the grid, so the next move has to reorient it again
+    if dropped_reorientation {
+        movement_data.oriented_to_cardinal_directions = false;
+    }
 }
 
 
 /// If command_queue is empty, do nothing
-/// Else (command queue has a command in it)
-/// If in_progress is None - create a new timer with Duration::from_seconds(MOVESTEP_DURATION)
-/// Else tick in_progress
-///     Execute current command
-/// If timer is empty, set movementdata.in_progress to None
+/// Else, if no command is in progress, start the one at the front of command_queue: work out where
+///     it ends and how long it takes (contextualize_current_movement), and start a timer for it
+///     - unless it is a step onto a blocked cell, which is dropped instead, and stops a held
+///     binding from repeating it
+/// Tick the timer and place the camera between where the command began and where it ends, by how
+///     far the timer has run - so the camera lands exactly on the next cell or cardinal direction
+///     however the ticks fall
+/// When the timer finishes, pop the command off command_queue
 pub fn execute_movement_queue(
     camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+    current_map: Option<Res<CurrentMap>>,
     mut movement_data: ResMut<ExplorationMovementData>,
+    mut step_completed: MessageWriter<MovementStepCompleted>,
     time: Res<Time>,
 ) {
-    // FIRST: if there is no current_movement_command
-    //      If command_queue.front() is none
-    //          return early
-    //      Else ()
-    //          calculate a new current_movement_command    ABSTRACT THIS TO NEW METHOD
-    //          start current_movement_timer for it
-    //
-    //
-    // Else: there is a current_movement_command;
-    //      there should be a timer already
-    //      interpolation based on it and the timer
-    //
-    //
-    // If current_movement_timer just_finished()
-    //      if command_queue.front() is Some
-    //          calculate a new current_movement_command    abstract to new method
-    //          start current_movement_timer for it
-    //      else
-    //          set current_movement to none
-    //          set current_movement_Timer to none
-    
-
-    // UPDATE THIS CODE: USE "CURRENT MOVEMENT" system; popping front of the command queue off and
-    // putting it in ExplorationMovementData::current_movement_command after prepping it with 
-    // contextualize_current_movement
-
-    
-    if movement_data.command_queue.front().is_none() { return; }
-   
-    // If we've made it this far - there IS movement enqueued.
-    // If there is not a timer, set the timer.
-    if movement_data.current_movement_timer.is_none() {
-        movement_data.current_movement_timer = Some(Timer::from_seconds(MOVESTEP_DURATION, TimerMode::Once));
-    } else {
-        // IF we've made it this far, there is an item in command queue and there is a
-        // timer- it must be manually ticked
-        movement_data.current_movement_timer.as_mut().unwrap().tick(time.delta());
-    }
-
     let mut camera_transform = camera_transform_q.into_inner();
-    let mut direction = Dir3::from_xyz(10., 10., 10.).unwrap();
-    let mut translated = false;
-    let mut rotated = 0.;
-
-    let current_command = movement_data.command_queue.front().unwrap();
-    match current_command {
-        ExplorationMovements::WalkForward => {
-            direction = -camera_transform.local_z();
-            translated = true;
-        },
-        ExplorationMovements::WalkBackward => {
-            direction = camera_transform.local_z();
-            translated = true;
-        },
-        ExplorationMovements::StrafeLeft => {
-            direction = -camera_transform.local_x();
-            translated = true;
-        },
-        ExplorationMovements::StrafeRight => {
-            direction = camera_transform.local_x();
-            translated = true;
-        },
-        ExplorationMovements::TurnClockw => {
-            let (cd, cd_angle) = cardinal_direction_rot_clockwise(movement_data.cardinal_facing.as_ref().unwrap());
-            info!("rotating to {:?}", cd);
-            movement_data.cardinal_facing = Some(cd);
-            rotated = cd_angle;
-        },
-        ExplorationMovements::TurnCounterclockw => {
-            let (cd, cd_angle) = cardinal_direction_rot_counterclockwise(movement_data.cardinal_facing.as_ref().unwrap());
-            info!("rotating to {:?}", cd);
-            movement_data.cardinal_facing = Some(cd);
-            rotated = cd_angle;
-        },
-        ExplorationMovements::FaceNorth => { 
-            rotated = CARDINAL_DIRECTION_ANGLES[0];
-            movement_data.cardinal_facing = Some(CardinalDirection::North);
-        }
-        ExplorationMovements::FaceEast => { 
-            rotated = CARDINAL_DIRECTION_ANGLES[1]; 
-            movement_data.cardinal_facing = Some(CardinalDirection::East);
-        }
-        ExplorationMovements::FaceSouth => { 
-            rotated = CARDINAL_DIRECTION_ANGLES[2]; 
-            movement_data.cardinal_facing = Some(CardinalDirection::South);
-        }
-        ExplorationMovements::FaceWest => { 
-            rotated = CARDINAL_DIRECTION_ANGLES[3]; 
-            movement_data.cardinal_facing = Some(CardinalDirection::West);
+    let movement_data = movement_data.as_mut();
+
+    if movement_data.current_movement_command.is_none() {
+        let next_movement = match movement_data.command_queue.front() {
+            Some(m) => *m,
+            None => return,
+        };
+        let grid = current_grid(current_map.as_deref());
+        let command = contextualize_current_movement(
+            &next_movement,
+            &camera_transform,
+            movement_data.cardinal_facing,
+            &grid
+        );
+        if let (Some(end_translation), Some(current_map)) = (command.end_translation, current_map.as_deref()) {
+            if cell_is_blocked(&current_map.definition, grid.world_to_cell(end_translation)) {
+                info!("Dropping {:?}, the way is blocked", next_movement);
+                movement_data.command_queue.pop_front();
+                movement_data.held_movement = None;
+                return;
+            }
         }
+        info!("executing {:?}", next_movement);
+        movement_data.cardinal_facing = facing_after(&next_movement, movement_data.cardinal_facing, grid.directions);
+        movement_data.current_movement_timer = Some(Timer::from_seconds(command.duration, TimerMode::Once));
+        movement_data.current_movement_command = Some(command);
+    }
+
+    let (current_command, timer) = match (
+        movement_data.current_movement_command.as_ref(),
+        movement_data.current_movement_timer.as_mut()
+    ) {
+        (Some(c), Some(t)) => (c, t),
+        _ => return,
+    };
+    timer.tick(time.delta());
+    let fraction = timer.fraction();
+
+    if let (Some(beginning), Some(end)) = (current_command.beginning_translation, current_command.end_translation) {
+        camera_transform.translation = beginning.lerp(end, fraction);
     }
-    
-    if translated {
-        camera_transform.translation += direction * MOVESTEP_DISTANCE * time.delta_secs();
-    } else if rotated != 0. {
-        // rotate_y needs to take in the delta of rotation in this tick, rather than the whole
-        // degree of rotation; thus we need to calculate the speed required to complete rotation by
-        // <rotated> over the course of <MOVESTEP_DURATION>.
-        let fraction = movement_data.current_movement_timer.as_ref().unwrap().fraction();
-        // let speed = 1.0 / MOVESTEP_DURATION;
-        // with rotate_local_y, I keep consistently getting greater than 90 degree rotation. I am
-        // going to see if working with Quaternions gets me better results.
-        // Otherwise - I wonder if I have to figure out if I will have more success with
-        // local/homogeneous coords.
-        // camera_transform.rotate_local_y(rotated * time.delta_secs() * speed);
-
-        let initial_rot = camera_transform.rotation;
-        let new_rot = initial_rot.lerp(Quat::from_rotation_y(rotated), fraction);
-        camera_transform.rotation = new_rot;
+    if let (Some(beginning), Some(end)) = (current_command.beginning_rotation, current_command.end_rotation) {
+        camera_transform.rotation = beginning.slerp(end, fraction);
     }
 
-    if movement_data.current_movement_timer.as_ref().unwrap().just_finished() {
+    if timer.is_finished() {
         info!("movement just finished");
-        movement_data.command_queue.pop_front();
-        if !movement_data.command_queue.is_empty() {
-            // movement_data.in_progress.as_mut().unwrap().set_duration(Duration::from_secs_f32(MOVESTEP_DURATION));
-            movement_data.current_movement_timer = Some(Timer::from_seconds(MOVESTEP_DURATION, TimerMode::Once));
-
-        } else {
-            movement_data.current_movement_timer = None;
+        if let MovementType::Translation = current_command.movement_type {
+            step_completed.write(MovementStepCompleted);
         }
+        movement_data.command_queue.pop_front();
+        movement_data.current_movement_command = None;
+        movement_data.current_movement_timer = None;
     }
-
 }
-
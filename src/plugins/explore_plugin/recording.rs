@@ -0,0 +1,292 @@
+// Recording exploration input, and playing it back, to reproduce bugs.
+//
+// A recording (ExplorationRecording, a RON file written through Reflect like saves) holds the map
+// and camera it started from and the game's RNG seeds (see RngSeeds), then every change the
+// player's input made to the movement queue (see movement::QueueInput), numbered by the fixed
+// timestep tick it was made on. Grid movement only advances in FixedUpdate, so making the same
+// changes on the same ticks (which is what playing it back does, with live movement input ignored)
+// ends exactly where the recording did. The recording keeps where the camera ended up, so a replay
+// can be checked against it; the tests replay every recording in src/tests/recordings this way.
+//
+// Nothing in the game is random yet, so RngSeeds is always empty: it is a hook reserved for the
+// first random system, which recordings already save and put back.
+//
+// Only what goes through the movement queue is recorded. Looking around with free-look isn't played
+// back, but the turn back to the grid that ends it is, so the camera still ends up facing the same
+// way. Continuous movement moves the camera directly, and menus, dialogue and quickloads aren't
+// recorded either, so a recording that used them won't play back the same.
+//
+// Recording and playback are started by --record and --replay (see launch_options).
+
+use std::{ collections::HashMap, fs, path::Path };
+use bevy::{
+    math::{ Quat, Vec3 },
+    prelude::{ Commands, Res, ResMut, Resource, Single, With, MessageReader, Reflect, Transform, error, info, warn },
+};
+
+use crate::{
+    launch_options::LaunchOptions,
+    reflect_ron::{ from_ron_str, to_ron_string },
+    plugins::{
+        camera_plugin::NavigateCamera,
+        explore_plugin::{
+            map::{ CurrentMap, current_grid },
+            movement::{
+                CardinalDirection, ExplorationMovementData, QueueInput,
+                cancel_queued_movements, enqueue_facing, enqueue_movement,
+            },
+        },
+    },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+
+// How far from the recorded end a replay may finish and still count as the same
+const END_TOLERANCE: f32 = 1e-3;
+
+
+/// The seed of each source of randomness in the game, by name. Nothing draws from it yet; anything
+/// random that is added should seed its generator from here (adding a seed of its own if there is
+/// none yet), so that a recording can put back the seeds it was made with and play back the same.
+#[derive(Resource, Reflect, Debug, Clone, Default, PartialEq)]
+pub struct RngSeeds(pub HashMap<String, u64>);
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub struct RecordedInput {
+    // fixed timestep ticks spent in Explore before this one, from the start of the recording
+    pub tick: u32,
+    pub input: QueueInput
+}
+
+#[derive(Reflect, Debug, Clone, PartialEq)]
+pub struct ExplorationRecording {
+    pub map_filepath: String,
+    pub start_translation: [f32; 3],
+    pub start_rotation: [f32; 4],
+    pub start_facing: Option<CardinalDirection>,
+    pub start_oriented: bool,
+    // RngSeeds when the recording started; empty in recordings made before it existed
+    #[reflect(default)]
+    pub rng_seeds: HashMap<String, u64>,
+    pub inputs: Vec<RecordedInput>,
+    // where the camera was once the last recorded movement finished
+    pub end_translation: [f32; 3],
+    pub end_facing: Option<CardinalDirection>
+}
+
+impl ExplorationRecording {
+    /// Whether a camera standing at transform, facing facing, is where the recording ended
+    pub fn ended_at(&self, transform: &Transform, facing: Option<CardinalDirection>) -> bool {
+        transform.translation.distance(Vec3::from_array(self.end_translation)) < END_TOLERANCE
+            && facing == self.end_facing
+    }
+}
+
+/// A recording being made. recording is None until the first tick in Explore, when where it starts
+/// from is known.
+#[derive(Resource)]
+pub struct ExplorationRecorder {
+    pub filepath: String,
+    pub recording: Option<ExplorationRecording>,
+    tick: u32,
+    unsaved: bool
+}
+
+/// A recording being played back
+#[derive(Resource)]
+pub struct ExplorationReplay {
+    pub recording: ExplorationRecording,
+    tick: u32,
+    next_input: usize,
+    checked_end: bool
+}
+
+impl ExplorationReplay {
+    pub fn new(recording: ExplorationRecording) -> Self {
+        ExplorationReplay { recording, tick: 0, next_input: 0, checked_end: false }
+    }
+
+    /// Every recorded input has been played back (its movement may still be executing)
+    pub fn finished(&self) -> bool {
+        self.next_input == self.recording.inputs.len()
+    }
+}
+
+
+/////////////////////////////////////////
+// FILE FUNCTIONS
+
+pub fn read_recording(filepath: &str) -> Result<ExplorationRecording, String> {
+    let recording_ron_str = fs::read_to_string(filepath).map_err(|e| e.to_string())?;
+    from_ron_str(&recording_ron_str)
+}
+
+pub fn write_recording(filepath: &str, recording: &ExplorationRecording) -> Result<(), String> {
+    if let Some(dirpath) = Path::new(filepath).parent() {
+        fs::create_dir_all(dirpath).map_err(|e| e.to_string())?;
+    }
+    let recording_ron_str = to_ron_string(recording)?;
+    fs::write(filepath, recording_ron_str).map_err(|e| e.to_string())
+}
+
+
+/////////////////////////////////////////
+// RUN CONDITIONS
+
+pub fn not_replaying(replay: Option<Res<ExplorationReplay>>) -> bool {
+    replay.is_none()
+}
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+/// Start recording, or load the recording to play back, as --record and --replay say. A new game
+/// started to replay a recording starts on the recording's map; replay_queue_input puts the camera
+/// where it started.
+pub fn setup_recording(mut launch_options: ResMut<LaunchOptions>, mut commands: Commands) {
+    if let Some(filepath) = launch_options.replay.clone() {
+        match read_recording(&filepath) {
+            Ok(recording) => {
+                info!("Replaying {} ({} inputs)", filepath, recording.inputs.len());
+                launch_options.map = Some(recording.map_filepath.clone());
+                commands.insert_resource(ExplorationReplay::new(recording));
+            }
+            Err(e) => error!("failure reading recording {}: {}", filepath, e),
+        }
+    }
+    if let Some(filepath) = &launch_options.record {
+        info!("Recording to {}", filepath);
+        commands.insert_resource(ExplorationRecorder {
+            filepath: filepath.clone(),
+            recording: None,
+            tick: 0,
+            unsaved: false
+        });
+    }
+}
+
+/// On the first tick in Explore, note where the recording starts from - before anything moves
+pub fn start_recording(
+    mut recorder: ResMut<ExplorationRecorder>,
+    rng_seeds: Res<RngSeeds>,
+    current_map: Option<Res<CurrentMap>>,
+    movement_data: Res<ExplorationMovementData>,
+    camera_transform_q: Single<&Transform, With<NavigateCamera>>,
+) {
+    if recorder.recording.is_some() {
+        return;
+    }
+    let current_map = match current_map {
+        Some(m) => m,
+        None => return,
+    };
+    let camera_transform = camera_transform_q.into_inner();
+    let recording = ExplorationRecording {
+        map_filepath: current_map.filepath.clone(),
+        start_translation: camera_transform.translation.to_array(),
+        start_rotation: camera_transform.rotation.to_array(),
+        start_facing: movement_data.cardinal_facing,
+        start_oriented: movement_data.oriented_to_cardinal_directions,
+        rng_seeds: rng_seeds.0.clone(),
+        inputs: Vec::new(),
+        end_translation: camera_transform.translation.to_array(),
+        end_facing: movement_data.cardinal_facing
+    };
+    if let Err(e) = write_recording(&recorder.filepath, &recording) {
+        error!("failure writing recording {}: {}", recorder.filepath, e);
+    }
+    recorder.recording = Some(recording);
+}
+
+/// Record this tick's changes to the movement queue. Each time the queue runs empty, note where the
+/// camera ended up and write the recording, so it is on disk however the game is left.
+pub fn record_queue_input(
+    mut recorder: ResMut<ExplorationRecorder>,
+    mut queue_input: MessageReader<QueueInput>,
+    movement_data: Res<ExplorationMovementData>,
+    camera_transform_q: Single<&Transform, With<NavigateCamera>>,
+) {
+    let recorder = recorder.as_mut();
+    let recording = match recorder.recording.as_mut() {
+        Some(r) => r,
+        None => return,
+    };
+    for input in queue_input.read() {
+        recording.inputs.push(RecordedInput { tick: recorder.tick, input: *input });
+        recorder.unsaved = true;
+    }
+    recorder.tick += 1;
+
+    let idle = movement_data.command_queue.is_empty() && movement_data.current_movement_timer.is_none();
+    if !recorder.unsaved || !idle {
+        return;
+    }
+    let camera_transform = camera_transform_q.into_inner();
+    recording.end_translation = camera_transform.translation.to_array();
+    recording.end_facing = movement_data.cardinal_facing;
+    match write_recording(&recorder.filepath, recording) {
+        Ok(()) => recorder.unsaved = false,
+        Err(e) => error!("failure writing recording {}: {}", recorder.filepath, e),
+    }
+}
+
+/// Put the camera where the recording started, and the RNG seeds back as they were, on the first
+/// tick. Then make each recorded change to the movement queue on the tick it was recorded on. Once
+/// everything has played back and finished moving, say whether the camera ended where the
+/// recording did.
+pub fn replay_queue_input(
+    mut replay: ResMut<ExplorationReplay>,
+    mut rng_seeds: ResMut<RngSeeds>,
+    current_map: Option<Res<CurrentMap>>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+) {
+    let replay = replay.as_mut();
+    let movement_data = movement_data.as_mut();
+    let mut camera_transform = camera_transform_q.into_inner();
+    let recording = &replay.recording;
+
+    if replay.tick == 0 {
+        if let Some(current_map) = current_map.as_deref() {
+            if current_map.filepath != recording.map_filepath {
+                warn!("replaying a recording of {} on {}", recording.map_filepath, current_map.filepath);
+            }
+        }
+        camera_transform.translation = Vec3::from_array(recording.start_translation);
+        camera_transform.rotation = Quat::from_array(recording.start_rotation);
+        movement_data.cardinal_facing = recording.start_facing;
+        movement_data.oriented_to_cardinal_directions = recording.start_oriented;
+        rng_seeds.0 = recording.rng_seeds.clone();
+    }
+
+    let directions = current_grid(current_map.as_deref()).directions;
+    while let Some(recorded) = recording.inputs.get(replay.next_input) {
+        if recorded.tick != replay.tick {
+            break;
+        }
+        match recorded.input {
+            QueueInput::Move(movement) => enqueue_movement(movement, movement_data, &camera_transform, directions),
+            QueueInput::Reorient(facing) => enqueue_facing(movement_data, facing),
+            QueueInput::CancelMoves => cancel_queued_movements(movement_data),
+        }
+        replay.next_input += 1;
+    }
+    replay.tick += 1;
+
+    let idle = movement_data.command_queue.is_empty() && movement_data.current_movement_timer.is_none();
+    if replay.finished() && idle && !replay.checked_end {
+        replay.checked_end = true;
+        if recording.ended_at(&camera_transform, movement_data.cardinal_facing) {
+            info!("Replay finished where the recording did");
+        } else {
+            warn!(
+                "Replay finished at {} facing {:?}, the recording at {:?} facing {:?}",
+                camera_transform.translation, movement_data.cardinal_facing,
+                recording.end_translation, recording.end_facing
+            );
+        }
+    }
+}
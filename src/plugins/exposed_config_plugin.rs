@@ -3,29 +3,62 @@
 
 use std::{ 
     fs, fs::OpenOptions, 
-    collections::HashMap,
+    collections::{ HashMap, HashSet },
+    fmt::Debug,
     io::Write,
-    any::TypeId,
 };
-use serde::{ de::DeserializeSeed };
 use bevy::{
-    reflect::{ FromReflect, PartialReflect, TypeRegistry,
-        serde::{ TypedReflectDeserializer, TypedReflectSerializer }
-    },
+    input::InputSystems,
     prelude::{ 
-        Commands, Resource, Res, Startup, App, Plugin, Reflect,
-        GamepadButton, KeyCode, error
+        Commands, Resource, Res, ResMut, Startup, PreUpdate, App, Plugin, Reflect, Query,
+        ButtonInput, Gamepad, GamepadButton, KeyCode,
+        IntoScheduleConfigs, SystemSet, warn
+    },
+};
+
+use crate::{
+    launch_options::LaunchOptions,
+    plugins::{
+        explore_plugin::continuous::MovementMode,
+        ui_plugin::localization::FALLBACK_LANGUAGE,
     },
+    reflect_ron::{ from_ron_str, to_ron_string },
 };
 
 // CONFIGURABLES
+// --config on the command line replaces this (see launch_options)
 const CONFIG_FILEPATH: &str = "config/game_config.ron";
+// The actions read on the same screen, by context. A key or button may be bound to actions of
+// different contexts (the gamepad's D-pad walks in Explore and moves focus in menus), but one bound
+// to two actions of the same context triggers both - read_exposed_config_file warns about those.
+// The character menu leaves out "Menu Left"/"Menu Right", which give way to the turns sharing
+// their buttons there (see character_menu_plugin).
+const ACTION_CONTEXTS: [(&str, &[&str]); 4] = [
+    ("Explore", &[
+        "Walk Forward", "Walk Backward", "Strafe Left", "Strafe Right", "Turn Left", "Turn Right",
+        "Turn Around", "Strafe Forward Left", "Strafe Forward Right", "Strafe Back Left",
+        "Strafe Back Right", "Menu", "Open Map", "Quicksave", "Quickload", "Interact",
+        "Character Menu", "Cancel Moves", "Free Look"
+    ]),
+    ("Dialogue", &["Walk Forward", "Walk Backward", "Interact", "Menu"]),
+    ("Character Menu", &[
+        "Turn Left", "Turn Right", "Strafe Left", "Strafe Right", "Character Menu", "Menu",
+        "Menu Up", "Menu Down", "Confirm", "Back"
+    ]),
+    ("Menus", &["Menu Up", "Menu Down", "Menu Left", "Menu Right", "Confirm", "Back", "Menu"]),
+];
 
 pub struct ExposedConfigPlugin;
 
 impl Plugin for ExposedConfigPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<LaunchOptions>();
+        app.init_resource::<ActionInput>();
         app.add_systems(Startup, load_exposed_config_file);
+        app.add_systems(
+            PreUpdate,
+            collect_action_input.in_set(ActionInputSystems).after(InputSystems)
+        );
     }
 }
 
@@ -35,7 +68,22 @@ impl Plugin for ExposedConfigPlugin {
 #[derive(Reflect, Debug, Resource)]
 pub struct ExposedConfig {
     pub keyboard_bindings: KeyboardBindings,
-    pub controller_bindings: ControllerBindings
+    pub controller_bindings: ControllerBindings,
+    pub autosave: AutosaveSettings,
+    #[reflect(default)]
+    pub movement: MovementSettings,
+    #[reflect(default)]
+    pub free_look: FreeLookSettings,
+    // language code of the string table in config/locales used for the UI
+    #[reflect(default = "default_language")]
+    pub language: String,
+    // the file this was read from, and is written back to
+    #[reflect(ignore)]
+    pub filepath: String
+}
+
+fn default_language() -> String {
+    String::from(FALLBACK_LANGUAGE)
 }
 
 #[derive(Reflect, Debug)]
@@ -47,6 +95,84 @@ pub struct ControllerBindings {
     pub exploration_controls: HashMap<String, GamepadButton>
 }
 
+// every_n_steps of 0 disables step-based autosaves.
+// rotating_slots is how many autosave files are cycled through before the oldest is overwritten.
+#[derive(Reflect, Debug)]
+pub struct AutosaveSettings {
+    pub on_map_enter: bool,
+    pub on_shop_exit: bool,
+    pub every_n_steps: u32,
+    pub rotating_slots: u8
+}
+
+// mode is Grid or Continuous, for maps that don't set their own.
+// max_queued_moves is how many moves can wait behind the one being made; presses beyond that are
+// dropped rather than walking on long after the key was let go.
+// Holding a movement binding repeats the move: first after repeat_delay seconds, then every
+// repeat_interval seconds - each repeat still waits for the step before it to finish, so an
+// interval of 0 walks on as soon as each step ends.
+// In continuous mode, the camera walks walk_speed units (a cell is the map's cell_size, 5 by
+// default) and the turn bindings turn it turn_speed degrees per second.
+#[derive(Reflect, Debug)]
+pub struct MovementSettings {
+    #[reflect(default)]
+    pub mode: MovementMode,
+    pub max_queued_moves: u8,
+    #[reflect(default = "default_repeat_delay")]
+    pub repeat_delay: f32,
+    #[reflect(default)]
+    pub repeat_interval: f32,
+    #[reflect(default = "default_walk_speed")]
+    pub walk_speed: f32,
+    #[reflect(default = "default_turn_speed")]
+    pub turn_speed: f32
+}
+
+fn default_repeat_delay() -> f32 {
+    0.4
+}
+
+fn default_walk_speed() -> f32 {
+    10.
+}
+
+fn default_turn_speed() -> f32 {
+    120.
+}
+
+impl Default for MovementSettings {
+    fn default() -> Self {
+        MovementSettings {
+            mode: MovementMode::Grid,
+            max_queued_moves: 2,
+            repeat_delay: default_repeat_delay(),
+            repeat_interval: 0.,
+            walk_speed: default_walk_speed(),
+            turn_speed: default_turn_speed()
+        }
+    }
+}
+
+// While "Free Look" is held (and always, in continuous movement), the mouse turns the camera
+// mouse_sensitivity degrees per pixel moved; while looking, the right stick turns it stick_speed
+// degrees per second at full tilt. The camera tilts at most max_pitch degrees up or down.
+#[derive(Reflect, Debug)]
+pub struct FreeLookSettings {
+    pub mouse_sensitivity: f32,
+    pub stick_speed: f32,
+    pub max_pitch: f32
+}
+
+impl Default for FreeLookSettings {
+    fn default() -> Self {
+        FreeLookSettings {
+            mouse_sensitivity: 0.15,
+            stick_speed: 120.,
+            max_pitch: 60.
+        }
+    }
+}
+
 impl ExposedConfig {
     // fn pack(&self) -> SerializedExposedConfig {
     //     SerializedExposedConfig {
@@ -56,40 +182,130 @@ impl ExposedConfig {
     // }
 }
 
-fn load_exposed_config_file(mut commands: Commands) {
-    let config_ron_str = fs::read_to_string(CONFIG_FILEPATH).unwrap_or_else(|err|panic!("{}", err));
-    
-    // make sure registry has the type
-    let mut type_registry = TypeRegistry::default();
-    type_registry.register::<ExposedConfig>();
-
-    // deserialize the RON string
-    let registration = type_registry.get(TypeId::of::<ExposedConfig>()).unwrap();
-    let mut deserializer = ron::de::Deserializer::from_str(&config_ron_str).unwrap_or_else(|e|panic!("{}",e));
-    let reflect_deserializer = TypedReflectDeserializer::new(registration, &type_registry);
-    let config_reflect_box: Box<dyn PartialReflect> = reflect_deserializer.deserialize(&mut deserializer).unwrap();
-    let config = ExposedConfig::from_reflect(&*config_reflect_box).unwrap();
+/// The set of collect_action_input and any system that filters ActionInput before it is read (e.g.
+/// blocking it while a screen transition plays). PreUpdate systems reading ActionInput run after
+/// this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActionInputSystems;
+
+/// Exploration actions (the keys of exploration_controls) whose keyboard key or gamepad button was
+/// just pressed this frame, collected once in PreUpdate.
+///
+/// A system handling an action should consume() it rather than reading the raw input, so that one
+/// press only ever has one meaning - a key held down does not retrigger, and pressing the keyboard
+/// and gamepad binding in the same frame (or a button on two gamepads) still counts once. Whether an
+/// action is still held down (e.g. to repeat it) is read with held().
+#[derive(Resource, Default)]
+pub struct ActionInput {
+    just_pressed: HashSet<String>,
+    held: HashSet<String>
+}
+
+impl ActionInput {
+    /// True if the action was just pressed and nothing has consumed it yet this frame
+    pub fn consume(&mut self, action: &str) -> bool {
+        self.just_pressed.remove(action)
+    }
+
+    /// True if the action was just pressed and nothing has consumed it yet this frame, leaving it
+    /// for whichever system handles it - for filters that drop one action in favour of another
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.just_pressed.contains(action)
+    }
+
+    /// True if the action's key or button is down this frame, whether or not it was just pressed
+    pub fn held(&self, action: &str) -> bool {
+        self.held.contains(action)
+    }
+
+    /// Drops every action pressed or held this frame, so none of them are handled
+    pub fn clear(&mut self) {
+        self.just_pressed.clear();
+        self.held.clear();
+    }
+}
+
+pub fn collect_action_input(
+    exposed_config: Res<ExposedConfig>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut action_input: ResMut<ActionInput>,
+) {
+    action_input.clear();
+
+    for (action, key) in &exposed_config.keyboard_bindings.exploration_controls {
+        if keyboard_input.just_pressed(*key) {
+            action_input.just_pressed.insert(action.clone());
+        }
+        if keyboard_input.pressed(*key) {
+            action_input.held.insert(action.clone());
+        }
+    }
+    for (action, button) in &exposed_config.controller_bindings.exploration_controls {
+        if gamepads.iter().any(|gamepad| gamepad.just_pressed(*button)) {
+            action_input.just_pressed.insert(action.clone());
+        }
+        if gamepads.iter().any(|gamepad| gamepad.pressed(*button)) {
+            action_input.held.insert(action.clone());
+        }
+    }
+}
+
+fn load_exposed_config_file(launch_options: Res<LaunchOptions>, mut commands: Commands) {
+    let filepath = launch_options.config.as_deref().unwrap_or(CONFIG_FILEPATH);
+    let config = read_exposed_config_file(filepath).unwrap_or_else(|e| panic!("{}", e));
     commands.insert_resource(config);
 }
 
-pub fn update_exposed_config_file(config: Res<ExposedConfig>) -> Result<String, String>{
+pub fn read_exposed_config_file(filepath: &str) -> Result<ExposedConfig, String> {
+    let config_ron_str = fs::read_to_string(filepath).map_err(|e| format!("{}: {}", filepath, e))?;
+    let mut config: ExposedConfig = from_ron_str(&config_ron_str).map_err(|e| format!("{}: {}", filepath, e))?;
+    config.filepath = String::from(filepath);
+    for shared in shared_bindings(&config) {
+        warn!("{}: {}", filepath, shared);
+    }
+    Ok(config)
+}
+
+/// Each key or button bound to two actions of one context in ACTION_CONTEXTS, described
+pub fn shared_bindings(config: &ExposedConfig) -> Vec<String> {
+    let mut shared = Vec::new();
+    for (context, actions) in ACTION_CONTEXTS {
+        shared_in_context(&config.keyboard_bindings.exploration_controls, "key", context, actions, &mut shared);
+        shared_in_context(&config.controller_bindings.exploration_controls, "button", context, actions, &mut shared);
+    }
+    shared
+}
+
+fn shared_in_context<T: PartialEq + Debug>(
+    bindings: &HashMap<String, T>,
+    device: &str,
+    context: &str,
+    actions: &[&str],
+    shared: &mut Vec<String>
+) {
+    for (index, first) in actions.iter().enumerate() {
+        for second in &actions[index + 1..] {
+            if let (Some(a), Some(b)) = (bindings.get(*first), bindings.get(*second)) {
+                if a == b {
+                    shared.push(format!("{} {:?} is bound to both {} and {} in {}", device, a, first, second, context));
+                }
+            }
+        }
+    }
+}
+
+pub fn update_exposed_config_file(config: &ExposedConfig) -> Result<String, String>{
     let mut file = OpenOptions::new()
         .write(true)
         .truncate(true)
-        .open(CONFIG_FILEPATH).unwrap_or_else(|err| panic!("{}", err));
+        .open(&config.filepath).unwrap_or_else(|err| panic!("{}", err));
     
-    // let type_registry = type_registry.read();
-    let type_registry = TypeRegistry::default();
-    let reflect_serializer = TypedReflectSerializer::new(config.as_partial_reflect(), &type_registry);
-    let new_config = ron::ser::to_string_pretty(
-        &reflect_serializer,
-        ron::ser::PrettyConfig::default()
-    ).unwrap_or_else(|err| panic!("{}", err));
+    let new_config = to_ron_string(config).unwrap_or_else(|err| panic!("{}", err));
 
     if file.write_all(new_config.as_bytes()).is_ok() {
         Ok(String::from("file write successful"))
     } else {
-        Err(String::from("error updating CONFIG_FILEPATH"))
+        Err(format!("error updating {}", config.filepath))
     }
 }
-
@@ -1,5 +1,6 @@
 //! This code does the following:
-//! 1. Declare GameModeState and InGameSubstate variants.
+//! 1. Declare GameModeState, InGameSubstate, PauseState, DialogueState and CharacterMenuState
+//!    variants.
 //! 2. Initialize app state.
 //! 3. Declare other plugins that define functionality on the related game modes.
 //!
@@ -7,16 +8,27 @@
 //!     - https://docs.rs/bevy/latest/bevy/state/state/trait.SubStates.html
 //!
 
-mod ingame_state_plugin;
+mod character_creation_plugin;
+mod character_menu_plugin;
+pub mod ingame_state_plugin;
 pub mod intro_screen_plugin;
 mod loadgame_menu_plugin;
 mod main_menu_plugin;
+mod pause_menu_plugin;
 
 use crate::plugins::manage_state_plugin::{
+    character_creation_plugin::CharacterCreationPlugin,
+    character_menu_plugin::CharacterMenuPlugin,
     ingame_state_plugin::InGameStatePlugin, 
     intro_screen_plugin::IntroScreenPlugin,
     loadgame_menu_plugin::LoadGameMenuPlugin,
     main_menu_plugin::MainMenuPlugin,
+    pause_menu_plugin::PauseMenuPlugin,
+};
+
+use crate::{
+    launch_options::{ LaunchOptions, LaunchState },
+    plugins::save_plugin::{ PendingLoad, SaveSlot, SavesDir, read_save },
 };
 
 use bevy::prelude::*;
@@ -27,6 +39,7 @@ pub enum GameModeState {
     #[default]
     IntroScreen,
     MainMenu,
+    CharacterCreation,
     LoadGameMenu,
     InGame
 }
@@ -43,25 +56,76 @@ pub enum InGameSubstate {
     Explore,
     // TEMPORARILY COMMENTED OUT THE BELOW TO FOCUS ON IMPLEMENTING EXPLORE STATE
     // Combat,
-    // Shop
+    Shop
+}
+
+// PauseState also only exists when app state is GameModeState::InGame, and sits alongside
+// InGameSubstate rather than replacing it, so pausing keeps the current substate's scene around.
+// While Paused, virtual time is paused - FixedUpdate (and so the movement queue) stops running.
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(GameModeState = GameModeState::InGame)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused
 }
 
-pub struct ManageStatePlugin {
-    pub start_ingame: bool
+// DialogueState is the dialogue overlay, which can open over Explore or Shop. Like PauseState it
+// sits alongside InGameSubstate. While Open, exploration movement input is blocked.
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(GameModeState = GameModeState::InGame)]
+pub enum DialogueState {
+    #[default]
+    Closed,
+    Open
 }
 
+// CharacterMenuState only exists while in InGameSubstate::Explore. While Open, the character menu
+// is drawn over the (still rendered) scene and exploration movement input is blocked.
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(InGameSubstate = InGameSubstate::Explore)]
+pub enum CharacterMenuState {
+    #[default]
+    Closed,
+    Open
+}
+
+// The state started in comes from LaunchOptions (--state and --load-slot on the command line)
+pub struct ManageStatePlugin;
+
 impl Plugin for ManageStatePlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<LaunchOptions>();
         app.init_state::<GameModeState>();
         app.add_sub_state::<InGameSubstate>();
-        app.add_plugins((IntroScreenPlugin, MainMenuPlugin, LoadGameMenuPlugin, InGameStatePlugin));
+        app.add_sub_state::<PauseState>();
+        app.add_sub_state::<DialogueState>();
+        app.add_sub_state::<CharacterMenuState>();
+        app.add_plugins((
+            IntroScreenPlugin, MainMenuPlugin, LoadGameMenuPlugin, InGameStatePlugin, PauseMenuPlugin,
+            CharacterMenuPlugin, CharacterCreationPlugin
+        ));
+        app.add_systems(Startup, switchstate_from_launch_options);
+    }
+}
 
-        if self.start_ingame {
-            app.add_systems(Startup, switchstate_ingame);
+fn switchstate_from_launch_options(
+    launch_options: Res<LaunchOptions>,
+    saves_dir: Res<SavesDir>,
+    mut pending_load: ResMut<PendingLoad>,
+    mut next_state: ResMut<NextState<GameModeState>>
+) {
+    if let Some(slot_index) = launch_options.load_slot {
+        let slot = SaveSlot::Manual(slot_index);
+        match read_save(&saves_dir, &slot) {
+            Ok(save) => pending_load.0 = Some(save),
+            Err(e) => error!("failure reading {} given by --load-slot, starting a new game: {}", slot.label(), e)
         }
     }
-}
 
-fn switchstate_ingame(mut next_state: ResMut<NextState<GameModeState>>) {
-    next_state.set(GameModeState::InGame);
+    match launch_options.start_state() {
+        LaunchState::Intro => {}
+        LaunchState::Menu => next_state.set(GameModeState::MainMenu),
+        LaunchState::InGame => next_state.set(GameModeState::InGame),
+    }
 }
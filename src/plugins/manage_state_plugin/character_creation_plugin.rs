@@ -0,0 +1,688 @@
+///// SPECS
+// - entered from the main menu's New button; builds the starting party from CREATION_FILEPATH
+// - one Member page per party member: type a name (or press Random Name to cycle the default
+//   names), pick a class and a portrait, and spend bonus points on the class's starting stats
+// - button NextButton moves to the next member, and after the last one to the Review page
+// - button BackButton moves to the previous member, and from the first one nextStates to MainMenu
+// - the Review page lists the whole party; EditButton goes back to the last member, and
+//   BeginButton hands the party to ingame_state_plugin (NewGameParty) and nextStates to InGame
+// - the Back action presses Back on the Member page and Edit on the Review page - except from the
+//   keyboard on the Member page, where Back's key (Backspace) deletes from the name instead
+//
+
+use std::fs;
+use bevy::{
+    input::{ ButtonState, keyboard::{ Key, KeyboardInput } },
+    prelude::*,
+};
+
+use crate::{
+    plugins::{
+        exposed_config_plugin::{ ActionInput, ActionInputSystems, ExposedConfig, collect_action_input },
+        manage_state_plugin::{
+            GameModeState,
+            ingame_state_plugin::party::{
+                EquipmentSlot, InventoryItem, NewGameParty, PartyMember, Skill, Stats
+            },
+        },
+        ui_plugin::{
+            focus::{ BackButton, InitialFocus },
+            localization::UiString,
+            theme::UiTheme,
+            widgets,
+        },
+    },
+    reflect_ron::from_ron_str,
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const CREATION_FILEPATH: &str = "config/party/character_creation.ron";
+const MAX_NAME_LENGTH: usize = 12;
+const STAT_NAME_KEYS: [&str; 4] = ["stat.strength", "stat.agility", "stat.intellect", "stat.vitality"];
+
+#[derive(Reflect, Resource, Debug, Clone)]
+pub struct CharacterCreationData {
+    pub party_size: usize,
+    /// Points each member can add to their class's starting stats
+    pub bonus_points: u32,
+    pub default_names: Vec<String>,
+    pub classes: Vec<CharacterClass>,
+    pub portraits: Vec<Portrait>
+}
+
+// stats, equipment, inventory and skills are what a member of this class starts with
+#[derive(Reflect, Debug, Clone)]
+pub struct CharacterClass {
+    pub name: String,
+    pub description: String,
+    pub stats: Stats,
+    #[reflect(default)]
+    pub equipment: Vec<EquipmentSlot>,
+    #[reflect(default)]
+    pub inventory: Vec<InventoryItem>,
+    #[reflect(default)]
+    pub skills: Vec<Skill>
+}
+
+// Shown as the image if there is one (relative to the assets folder), otherwise as color
+#[derive(Reflect, Debug, Clone)]
+pub struct Portrait {
+    pub name: String,
+    pub color: Color,
+    #[reflect(default)]
+    pub image: Option<String>
+}
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(GameModeState = GameModeState::CharacterCreation)]
+enum CreationPage {
+    #[default]
+    Member,
+    Review
+}
+
+pub struct CharacterCreationPlugin;
+
+impl Plugin for CharacterCreationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<CreationPage>();
+        app.insert_resource(load_creation_data());
+        app.init_resource::<CharacterDrafts>();
+
+        app.add_systems(OnEnter(GameModeState::CharacterCreation), setup_charactercreation);
+        app.add_systems(OnExit(GameModeState::CharacterCreation), cleanup_charactercreation);
+        app.add_systems(OnEnter(CreationPage::Member), setup_member_page);
+        app.add_systems(OnEnter(CreationPage::Review), setup_review_page);
+        app.add_systems(OnExit(CreationPage::Member), cleanup_page);
+        app.add_systems(OnExit(CreationPage::Review), cleanup_page);
+
+        app.add_systems(
+            PreUpdate,
+            skip_back_key_while_naming
+                .in_set(ActionInputSystems)
+                .after(collect_action_input)
+                .run_if(in_state(CreationPage::Member))
+        );
+
+        app.add_systems(
+            Update,
+            (
+                type_character_name.run_if(in_state(CreationPage::Member)),
+                charactercreation_action_system,
+                update_draft_labels.run_if(resource_changed::<CharacterDrafts>)
+            ).chain().run_if(in_state(GameModeState::CharacterCreation))
+        );
+    }
+}
+
+
+/////////////////////////////////////////
+// NODE STRUCTURE
+
+#[derive(Component)]
+struct CharacterCreationRootNode;
+
+// Pages are spawned into (and despawned from) this panel as CreationPage changes
+#[derive(Component)]
+struct CreationPanel;
+
+#[derive(Component)]
+struct CreationPageNode;
+
+// The party being made. class and portrait index into CharacterCreationData; bonus is the points
+// added to each of STAT_NAME_KEYS.
+#[derive(Clone)]
+struct CharacterDraft {
+    name: String,
+    class: usize,
+    portrait: usize,
+    bonus: [u32; 4]
+}
+
+#[derive(Resource, Default)]
+struct CharacterDrafts {
+    members: Vec<CharacterDraft>,
+    // the member being edited on the Member page
+    current: usize
+}
+
+// Text on the Member page that shows part of the current draft
+#[derive(Component, Clone, Copy)]
+enum DraftLabel {
+    Heading,
+    Name,
+    Class,
+    ClassDescription,
+    Portrait,
+    Stat(usize),
+    PointsLeft
+}
+
+#[derive(Component)]
+struct PortraitSwatch;
+
+fn setup_charactercreation(
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    creation_data: Res<CharacterCreationData>,
+    mut drafts: ResMut<CharacterDrafts>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let ui_camera = match camera_query.single() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    *drafts = initial_drafts(&creation_data);
+
+    commands.spawn((
+        CharacterCreationRootNode,
+        widgets::screen_root(&theme, ui_camera),
+        children![(CreationPanel, widgets::panel(&theme))]
+    ));
+}
+
+fn cleanup_charactercreation(
+    query: Query<Entity, With<CharacterCreationRootNode>>,
+    mut commands: Commands
+) {
+    let charactercreation_rootnode = match query.single() {
+        Ok(n) => n,
+        Err(_) => return,
+    };
+
+    commands
+        .entity(charactercreation_rootnode)
+        .despawn();
+}
+
+fn setup_member_page(
+    panel_query: Query<Entity, With<CreationPanel>>,
+    creation_data: Res<CharacterCreationData>,
+    drafts: Res<CharacterDrafts>,
+    theme: Res<UiTheme>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands
+) {
+    let panel = match panel_query.single() {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+    let label = |draft_label: DraftLabel| draft_label_text(draft_label, &drafts, &creation_data);
+
+    commands.entity(panel).with_children(|panel| {
+        panel.spawn((CreationPageNode, widgets::column(&theme))).with_children(|page| {
+            page.spawn((DraftLabel::Heading, widgets::title_text(&theme, label(DraftLabel::Heading))));
+
+            page.spawn(widgets::button_row(&theme)).with_children(|row| {
+                row.spawn(value_label(&theme, DraftLabel::Name, label(DraftLabel::Name)));
+                row.spawn((
+                    CharacterCreationButtonAction::RandomName,
+                    widgets::compact_button(&theme, UiString::key("creation.random_name"))
+                ));
+            });
+
+            page.spawn(value_row(
+                &theme,
+                CharacterCreationButtonAction::PrevClass,
+                value_label(&theme, DraftLabel::Class, label(DraftLabel::Class)),
+                CharacterCreationButtonAction::NextClass
+            ));
+            page.spawn((DraftLabel::ClassDescription, widgets::small_text(&theme, label(DraftLabel::ClassDescription))));
+
+            let portrait = &creation_data.portraits[drafts.members[drafts.current].portrait];
+            page.spawn(value_row(
+                &theme,
+                CharacterCreationButtonAction::PrevPortrait,
+                (
+                    label_box(&theme),
+                    children![
+                        (
+                            PortraitSwatch,
+                            Node {
+                                width: Val::Px(theme.sizes.row_height * 0.6),
+                                height: Val::Px(theme.sizes.row_height * 0.6),
+                                margin: UiRect::right(Val::Px(theme.sizes.gap)),
+                                ..default()
+                            },
+                            BackgroundColor(portrait.color),
+                            portrait_image(portrait, &asset_server)
+                        ),
+                        (DraftLabel::Portrait, widgets::body_text(&theme, label(DraftLabel::Portrait)))
+                    ]
+                ),
+                CharacterCreationButtonAction::NextPortrait
+            ));
+
+            for index in 0..STAT_NAME_KEYS.len() {
+                page.spawn(value_row(
+                    &theme,
+                    CharacterCreationButtonAction::DecreaseStat(index),
+                    value_label(&theme, DraftLabel::Stat(index), label(DraftLabel::Stat(index))),
+                    CharacterCreationButtonAction::IncreaseStat(index)
+                ));
+            }
+            page.spawn((DraftLabel::PointsLeft, widgets::small_text(&theme, label(DraftLabel::PointsLeft))));
+
+            page.spawn((
+                widgets::button_row(&theme),
+                children![
+                    (
+                        CharacterCreationButtonAction::Back,
+                        BackButton,
+                        widgets::button(&theme, UiString::key("creation.back"))
+                    ),
+                    (
+                        CharacterCreationButtonAction::Next,
+                        InitialFocus,
+                        widgets::button(&theme, UiString::key("creation.next"))
+                    )
+                ]
+            ));
+        });
+    });
+}
+
+fn setup_review_page(
+    panel_query: Query<Entity, With<CreationPanel>>,
+    creation_data: Res<CharacterCreationData>,
+    drafts: Res<CharacterDrafts>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let panel = match panel_query.single() {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+
+    commands.entity(panel).with_children(|panel| {
+        panel.spawn((CreationPageNode, widgets::column(&theme))).with_children(|page| {
+            page.spawn(widgets::title_text(&theme, UiString::key("creation.your_party")));
+            for member in drafts.members.iter().map(|draft| build_party_member(draft, &creation_data)) {
+                page.spawn(widgets::body_text(&theme, UiString::key_with(
+                    "creation.review_member",
+                    [member.name, member.class, member.level.to_string()]
+                )));
+                page.spawn(widgets::small_text(&theme, UiString::key_with(
+                    "creation.review_stats",
+                    [
+                        member.stats.max_hp, member.stats.max_mp, member.stats.strength,
+                        member.stats.agility, member.stats.intellect, member.stats.vitality
+                    ]
+                )));
+            }
+            page.spawn((
+                widgets::button_row(&theme),
+                children![
+                    (
+                        CharacterCreationButtonAction::Edit,
+                        BackButton,
+                        widgets::button(&theme, UiString::key("creation.edit"))
+                    ),
+                    (
+                        CharacterCreationButtonAction::Begin,
+                        InitialFocus,
+                        widgets::button(&theme, UiString::key("creation.begin"))
+                    )
+                ]
+            ));
+        });
+    });
+}
+
+fn cleanup_page(
+    query: Query<Entity, With<CreationPageNode>>,
+    mut commands: Commands
+) {
+    for page in &query {
+        commands.entity(page).despawn();
+    }
+}
+
+// Redraws the Member page's labels (and the portrait swatch) whenever a draft changes
+fn update_draft_labels(
+    creation_data: Res<CharacterCreationData>,
+    drafts: Res<CharacterDrafts>,
+    asset_server: Res<AssetServer>,
+    mut label_query: Query<(&DraftLabel, &mut UiString)>,
+    mut swatch_query: Query<(&mut BackgroundColor, &mut ImageNode), With<PortraitSwatch>>,
+) {
+    if drafts.members.is_empty() {
+        return;
+    }
+
+    for (draft_label, mut ui_string) in &mut label_query {
+        ui_string.set_if_neq(draft_label_text(*draft_label, &drafts, &creation_data));
+    }
+
+    let portrait = &creation_data.portraits[drafts.members[drafts.current].portrait];
+    for (mut background_color, mut image_node) in &mut swatch_query {
+        background_color.0 = portrait.color;
+        *image_node = portrait_image(portrait, &asset_server);
+    }
+}
+
+
+/////////////////////////////////////////
+// BUTTON FUNCTIONALITY
+
+#[derive(Component)]
+enum CharacterCreationButtonAction {
+    RandomName,
+    PrevClass,
+    NextClass,
+    PrevPortrait,
+    NextPortrait,
+    DecreaseStat(usize),
+    IncreaseStat(usize),
+    Back,
+    Next,
+    Edit,
+    Begin
+}
+
+fn charactercreation_action_system(
+    interaction_query: Query<
+        (&Interaction, &CharacterCreationButtonAction),
+        (Changed<Interaction>, With<Button>)
+    >,
+    creation_data: Res<CharacterCreationData>,
+    mut drafts: ResMut<CharacterDrafts>,
+    mut new_game_party: ResMut<NewGameParty>,
+    mut next_game_mode_state: ResMut<NextState<GameModeState>>,
+    mut next_page: ResMut<NextState<CreationPage>>
+) {
+    for (interaction, button_action) in &interaction_query {
+        if interaction != &Interaction::Pressed {
+            continue;
+        }
+
+        let current = drafts.current;
+        let party_size = drafts.members.len();
+        match button_action {
+            CharacterCreationButtonAction::RandomName => {
+                let names = &creation_data.default_names;
+                let draft = &mut drafts.members[current];
+                let next = names.iter()
+                    .position(|n| *n == draft.name)
+                    .map(|i| i + 1)
+                    .unwrap_or(current);
+                if !names.is_empty() {
+                    draft.name = names[next % names.len()].clone();
+                }
+            },
+            CharacterCreationButtonAction::PrevClass => {
+                let draft = &mut drafts.members[current];
+                draft.class = cycle(draft.class, creation_data.classes.len(), -1);
+            },
+            CharacterCreationButtonAction::NextClass => {
+                let draft = &mut drafts.members[current];
+                draft.class = cycle(draft.class, creation_data.classes.len(), 1);
+            },
+            CharacterCreationButtonAction::PrevPortrait => {
+                let draft = &mut drafts.members[current];
+                draft.portrait = cycle(draft.portrait, creation_data.portraits.len(), -1);
+            },
+            CharacterCreationButtonAction::NextPortrait => {
+                let draft = &mut drafts.members[current];
+                draft.portrait = cycle(draft.portrait, creation_data.portraits.len(), 1);
+            },
+            CharacterCreationButtonAction::DecreaseStat(index) => {
+                let draft = &mut drafts.members[current];
+                draft.bonus[*index] = draft.bonus[*index].saturating_sub(1);
+            },
+            CharacterCreationButtonAction::IncreaseStat(index) => {
+                let draft = &mut drafts.members[current];
+                if points_left(draft, &creation_data) > 0 {
+                    draft.bonus[*index] += 1;
+                }
+            },
+            CharacterCreationButtonAction::Back => {
+                match current {
+                    0 => next_game_mode_state.set(GameModeState::MainMenu),
+                    _ => drafts.current -= 1,
+                }
+            },
+            CharacterCreationButtonAction::Next => {
+                if current + 1 < party_size {
+                    drafts.current += 1;
+                } else {
+                    next_page.set(CreationPage::Review);
+                }
+            },
+            CharacterCreationButtonAction::Edit => {
+                next_page.set(CreationPage::Member);
+            },
+            CharacterCreationButtonAction::Begin => {
+                new_game_party.0 = Some(
+                    drafts.members.iter().map(|draft| build_party_member(draft, &creation_data)).collect()
+                );
+                next_game_mode_state.set(GameModeState::InGame);
+            },
+        }
+    }
+}
+
+// The name field takes typed keys, so the keyboard's "Back" binding is left to it rather than
+// pressing Back - unless a gamepad's "Back" button was pressed too
+fn skip_back_key_while_naming(
+    exposed_config: Res<ExposedConfig>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut action_input: ResMut<ActionInput>
+) {
+    let key_pressed = exposed_config.keyboard_bindings.exploration_controls
+        .get("Back")
+        .is_some_and(|key| keyboard_input.just_pressed(*key));
+    let button_pressed = exposed_config.controller_bindings.exploration_controls
+        .get("Back")
+        .is_some_and(|button| gamepads.iter().any(|gamepad| gamepad.just_pressed(*button)));
+    if key_pressed && !button_pressed {
+        action_input.consume("Back");
+    }
+}
+
+// Typed characters are added to the current member's name; Backspace removes the last one
+fn type_character_name(
+    mut keyboard_input: MessageReader<KeyboardInput>,
+    mut drafts: ResMut<CharacterDrafts>
+) {
+    let current = drafts.current;
+    for input in keyboard_input.read() {
+        if input.state != ButtonState::Pressed {
+            continue;
+        }
+        let draft = match drafts.members.get_mut(current) {
+            Some(d) => d,
+            None => return,
+        };
+        match &input.logical_key {
+            Key::Backspace => {
+                draft.name.pop();
+            },
+            Key::Space if draft.name.chars().count() < MAX_NAME_LENGTH => {
+                draft.name.push(' ');
+            },
+            Key::Character(typed) => {
+                for c in typed.chars().filter(|c| c.is_alphanumeric() || *c == '-' || *c == '\'') {
+                    if draft.name.chars().count() < MAX_NAME_LENGTH {
+                        draft.name.push(c);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+// Falls back to a single plain class and portrait, so New Game still works without the file
+fn load_creation_data() -> CharacterCreationData {
+    let read = fs::read_to_string(CREATION_FILEPATH)
+        .map_err(|e| e.to_string())
+        .and_then(|ron_str| from_ron_str::<CharacterCreationData>(&ron_str))
+        .and_then(|data| match data.classes.is_empty() || data.portraits.is_empty() {
+            true => Err(String::from("needs at least one class and one portrait")),
+            false => Ok(data),
+        });
+    match read {
+        Ok(d) => d,
+        Err(e) => {
+            error!("failure loading {}, using a single default class: {}", CREATION_FILEPATH, e);
+            CharacterCreationData {
+                party_size: 1,
+                bonus_points: 0,
+                default_names: Vec::new(),
+                classes: vec![CharacterClass {
+                    name: String::from("Adventurer"),
+                    description: String::new(),
+                    stats: Stats {
+                        hp: 20, max_hp: 20, mp: 5, max_mp: 5,
+                        strength: 10, agility: 10, intellect: 10, vitality: 10
+                    },
+                    equipment: Vec::new(),
+                    inventory: Vec::new(),
+                    skills: Vec::new()
+                }],
+                portraits: vec![Portrait {
+                    name: String::from("Plain"),
+                    color: Color::srgb(0.5, 0.5, 0.5),
+                    image: None
+                }]
+            }
+        }
+    }
+}
+
+fn initial_drafts(creation_data: &CharacterCreationData) -> CharacterDrafts {
+    let members = (0..creation_data.party_size.max(1))
+        .map(|index| CharacterDraft {
+            name: creation_data.default_names.get(index).cloned().unwrap_or_default(),
+            class: index % creation_data.classes.len(),
+            portrait: index % creation_data.portraits.len(),
+            bonus: [0; 4]
+        })
+        .collect();
+    CharacterDrafts { members, current: 0 }
+}
+
+// A member left unnamed is called by their class
+fn build_party_member(draft: &CharacterDraft, creation_data: &CharacterCreationData) -> PartyMember {
+    let class = &creation_data.classes[draft.class];
+    let mut stats = class.stats.clone();
+    for (index, bonus) in draft.bonus.iter().enumerate() {
+        *stat_mut(&mut stats, index) += bonus;
+    }
+    PartyMember {
+        name: match draft.name.trim().is_empty() {
+            true => class.name.clone(),
+            false => draft.name.trim().to_string(),
+        },
+        class: class.name.clone(),
+        portrait: creation_data.portraits[draft.portrait].name.clone(),
+        level: 1,
+        stats,
+        equipment: class.equipment.clone(),
+        inventory: class.inventory.clone(),
+        skills: class.skills.clone()
+    }
+}
+
+fn draft_label_text(draft_label: DraftLabel, drafts: &CharacterDrafts, creation_data: &CharacterCreationData) -> UiString {
+    let draft = &drafts.members[drafts.current];
+    let class = &creation_data.classes[draft.class];
+    match draft_label {
+        DraftLabel::Heading => UiString::key_with(
+            "creation.heading",
+            [(drafts.current + 1).to_string(), drafts.members.len().to_string()]
+        ),
+        DraftLabel::Name => UiString::key_with("creation.name", [&draft.name]),
+        DraftLabel::Class => UiString::from(&class.name),
+        DraftLabel::ClassDescription => UiString::from(&class.description),
+        DraftLabel::Portrait => UiString::from(&creation_data.portraits[draft.portrait].name),
+        DraftLabel::Stat(index) => {
+            let mut stats = class.stats.clone();
+            let base = *stat_mut(&mut stats, index);
+            match draft.bonus[index] {
+                0 => UiString::key_with("creation.stat", [UiString::key(STAT_NAME_KEYS[index]), base.into()]),
+                bonus => UiString::key_with(
+                    "creation.stat_bonus",
+                    [UiString::key(STAT_NAME_KEYS[index]), (base + bonus).into(), bonus.into()]
+                ),
+            }
+        },
+        DraftLabel::PointsLeft => UiString::key_with("creation.points_left", [points_left(draft, creation_data)]),
+    }
+}
+
+fn points_left(draft: &CharacterDraft, creation_data: &CharacterCreationData) -> u32 {
+    creation_data.bonus_points.saturating_sub(draft.bonus.iter().sum())
+}
+
+fn stat_mut(stats: &mut Stats, index: usize) -> &mut u32 {
+    match index {
+        0 => &mut stats.strength,
+        1 => &mut stats.agility,
+        2 => &mut stats.intellect,
+        _ => &mut stats.vitality,
+    }
+}
+
+fn cycle(index: usize, len: usize, step: isize) -> usize {
+    (index as isize + step).rem_euclid(len.max(1) as isize) as usize
+}
+
+// Without an image the node is see-through, showing the swatch's colour
+fn portrait_image(portrait: &Portrait, asset_server: &AssetServer) -> ImageNode {
+    match &portrait.image {
+        Some(path) => ImageNode::new(asset_server.load(path)),
+        None => ImageNode { color: Color::NONE, ..default() }
+    }
+}
+
+fn value_label(theme: &UiTheme, draft_label: DraftLabel, text: UiString) -> impl Bundle {
+    (
+        label_box(theme),
+        children![(draft_label, widgets::body_text(theme, text))]
+    )
+}
+
+// Fixed width, so the buttons either side of a value stay put as it changes
+fn label_box(theme: &UiTheme) -> Node {
+    Node {
+        width: Val::Px(theme.sizes.button_width),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+// "<" value ">"
+fn value_row(
+    theme: &UiTheme,
+    prev_action: CharacterCreationButtonAction,
+    value: impl Bundle,
+    next_action: CharacterCreationButtonAction
+) -> impl Bundle {
+    let (prev_text, next_text) = match prev_action {
+        CharacterCreationButtonAction::DecreaseStat(_) => ("-", "+"),
+        _ => ("<", ">"),
+    };
+    (
+        widgets::button_row(theme),
+        children![
+            (prev_action, widgets::compact_button(theme, prev_text)),
+            value,
+            (next_action, widgets::compact_button(theme, next_text))
+        ]
+    )
+}
@@ -0,0 +1,395 @@
+///// SPECS
+// - "Character Menu" binding opens the menu from Explore (when no dialogue is open and the game
+//   isn't paused); "Character Menu" or "Menu" closes it
+// - the menu is an overlay - the 3D scene keeps rendering behind it, but movement input is blocked
+// - one tab each for Stats, Equipment, Inventory and Skills, showing the selected party member
+// - "Turn Left"/"Turn Right" switch tab, "Strafe Left"/"Strafe Right" switch party member; tabs and
+//   members can also be clicked
+// - a press of "Turn Left"/"Turn Right" that is also "Menu Left"/"Menu Right" (the gamepad's D-pad)
+//   only switches tab, rather than moving focus as well
+// - the Back action presses Close
+//
+
+use crate::plugins::{
+    manage_state_plugin::{
+        CharacterMenuState, DialogueState, InGameSubstate, PauseState,
+        ingame_state_plugin::{ InGameData, party::PartyMember },
+        pause_menu_plugin::toggle_pause_system,
+    },
+    exposed_config_plugin::{ ActionInput, ActionInputSystems, collect_action_input },
+    explore_plugin::movement::{ ExplorationMovementData, cancel_queued_movements },
+    ui_plugin::{
+        focus::BackButton,
+        localization::UiString,
+        theme::UiTheme,
+        widgets::{ self, UiSelected },
+    },
+};
+use bevy::prelude::*;
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const TABS: [CharacterMenuTab; 4] = [
+    CharacterMenuTab::Stats,
+    CharacterMenuTab::Equipment,
+    CharacterMenuTab::Inventory,
+    CharacterMenuTab::Skills
+];
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+// Which tab of the character menu is showing. Only exists while the menu is open.
+#[derive(SubStates, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[source(CharacterMenuState = CharacterMenuState::Open)]
+enum CharacterMenuTab {
+    #[default]
+    Stats,
+    Equipment,
+    Inventory,
+    Skills
+}
+
+// Index into InGameData.party of the member being shown
+#[derive(Resource, Default)]
+struct CharacterMenuMember(usize);
+
+pub struct CharacterMenuPlugin;
+
+impl Plugin for CharacterMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<CharacterMenuTab>();
+        app.init_resource::<CharacterMenuMember>();
+
+        app.add_systems(
+            Update,
+            toggle_character_menu_system
+                .before(toggle_pause_system)
+                .run_if(in_state(InGameSubstate::Explore))
+                .run_if(in_state(DialogueState::Closed))
+                .run_if(in_state(PauseState::Running))
+        );
+        app.add_systems(
+            PreUpdate,
+            turns_take_shared_menu_presses
+                .in_set(ActionInputSystems)
+                .after(collect_action_input)
+                .run_if(in_state(CharacterMenuState::Open))
+        );
+        app.add_systems(OnEnter(CharacterMenuState::Open), setup_character_menu);
+        app.add_systems(OnExit(CharacterMenuState::Open), cleanup_character_menu);
+
+        app.add_systems(
+            Update,
+            (
+                character_menu_controls_system,
+                character_menu_action_system,
+                refresh_character_menu_content
+                    .run_if(state_changed::<CharacterMenuTab>.or(resource_changed::<CharacterMenuMember>)),
+                mark_selected_buttons,
+            )
+                .chain()
+                .run_if(in_state(CharacterMenuState::Open))
+                .run_if(in_state(PauseState::Running))
+        );
+    }
+}
+
+
+/////////////////////////////////////////
+// OPENING AND CLOSING
+
+fn toggle_character_menu_system(
+    mut action_input: ResMut<ActionInput>,
+    character_menu_state: Res<State<CharacterMenuState>>,
+    mut next_character_menu_state: ResMut<NextState<CharacterMenuState>>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+) {
+    match character_menu_state.get() {
+        CharacterMenuState::Closed => {
+            if action_input.consume("Character Menu") {
+                cancel_queued_movements(&mut movement_data);
+                next_character_menu_state.set(CharacterMenuState::Open);
+            }
+        },
+        CharacterMenuState::Open => {
+            // consume both, so "Menu" closes this rather than also opening the pause menu
+            let close_pressed = action_input.consume("Character Menu");
+            if action_input.consume("Menu") || close_pressed {
+                next_character_menu_state.set(CharacterMenuState::Closed);
+            }
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// NODE STRUCTURE
+
+#[derive(Component)]
+struct CharacterMenuRootNode;
+
+// The selected tab's contents are spawned into (and despawned from) this node
+#[derive(Component)]
+struct CharacterMenuContent;
+
+fn setup_character_menu(
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    ingame_data: Res<InGameData>,
+    mut member: ResMut<CharacterMenuMember>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    member.0 = 0;
+
+    let ui_camera = match camera_query.single() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    commands.spawn((
+        CharacterMenuRootNode,
+        widgets::overlay_root(&theme, ui_camera),
+    )).with_children(|root| {
+        root.spawn((
+            Node {
+                width: Val::Percent(70.0),
+                height: Val::Percent(80.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(theme.sizes.padding)),
+                row_gap: Val::Px(theme.sizes.gap),
+                ..default()
+            },
+            BackgroundColor(theme.colors.panel_background),
+        )).with_children(|panel| {
+            // party member row
+            panel.spawn(widgets::button_row(&theme)).with_children(|row| {
+                for (index, member) in ingame_data.party.iter().enumerate() {
+                    row.spawn((
+                        CharacterMenuButtonAction::Member(index),
+                        widgets::compact_button(&theme, &member.name)
+                    ));
+                }
+            });
+            // tab row
+            panel.spawn(widgets::button_row(&theme)).with_children(|row| {
+                for tab in TABS {
+                    row.spawn((
+                        CharacterMenuButtonAction::Tab(tab),
+                        widgets::compact_button(&theme, tab_label(tab))
+                    ));
+                }
+                row.spawn((
+                    CharacterMenuButtonAction::Close,
+                    BackButton,
+                    widgets::compact_button(&theme, UiString::key("character.close"))
+                ));
+            });
+            panel.spawn((
+                CharacterMenuContent,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    flex_grow: 1.,
+                    row_gap: Val::Px(theme.sizes.gap * 0.5),
+                    padding: UiRect::all(Val::Px(theme.sizes.padding * 0.5)),
+                    ..default()
+                }
+            ));
+        });
+    });
+}
+
+fn cleanup_character_menu(
+    query: Query<Entity, With<CharacterMenuRootNode>>,
+    mut commands: Commands
+) {
+    for character_menu_rootnode in &query {
+        commands.entity(character_menu_rootnode).despawn();
+    }
+}
+
+fn refresh_character_menu_content(
+    content_query: Query<Entity, With<CharacterMenuContent>>,
+    tab: Res<State<CharacterMenuTab>>,
+    member: Res<CharacterMenuMember>,
+    ingame_data: Res<InGameData>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let content = match content_query.single() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    let lines = match ingame_data.party.get(member.0) {
+        Some(party_member) => tab_lines(*tab.get(), party_member),
+        None => vec![UiString::key("character.no_party")],
+    };
+
+    commands.entity(content).despawn_related::<Children>();
+    commands.entity(content).with_children(|parent| {
+        for line in lines {
+            parent.spawn(widgets::body_text(&theme, line));
+        }
+    });
+}
+
+
+/////////////////////////////////////////
+// BUTTON FUNCTIONALITY
+
+#[derive(Component)]
+enum CharacterMenuButtonAction {
+    Member(usize),
+    Tab(CharacterMenuTab),
+    Close
+}
+
+// "Turn Left"/"Turn Right" and "Menu Left"/"Menu Right" share the D-pad on a gamepad; here the
+// turns switch tab, so the focus is left where it is
+fn turns_take_shared_menu_presses(mut action_input: ResMut<ActionInput>) {
+    for (turn, menu) in [("Turn Left", "Menu Left"), ("Turn Right", "Menu Right")] {
+        if action_input.just_pressed(turn) {
+            action_input.consume(menu);
+        }
+    }
+}
+
+fn character_menu_controls_system(
+    mut action_input: ResMut<ActionInput>,
+    tab: Res<State<CharacterMenuTab>>,
+    mut next_tab: ResMut<NextState<CharacterMenuTab>>,
+    mut member: ResMut<CharacterMenuMember>,
+    ingame_data: Res<InGameData>,
+) {
+    let tab_index = TABS.iter().position(|t| t == tab.get()).unwrap_or(0);
+    if action_input.consume("Turn Left") {
+        next_tab.set(TABS[(tab_index + TABS.len() - 1) % TABS.len()]);
+    }
+    if action_input.consume("Turn Right") {
+        next_tab.set(TABS[(tab_index + 1) % TABS.len()]);
+    }
+
+    let party_size = ingame_data.party.len();
+    if party_size == 0 {
+        return;
+    }
+    if action_input.consume("Strafe Left") {
+        member.0 = (member.0 + party_size - 1) % party_size;
+    }
+    if action_input.consume("Strafe Right") {
+        member.0 = (member.0 + 1) % party_size;
+    }
+}
+
+fn character_menu_action_system(
+    interaction_query: Query<
+        (&Interaction, &CharacterMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut member: ResMut<CharacterMenuMember>,
+    mut next_tab: ResMut<NextState<CharacterMenuTab>>,
+    mut next_character_menu_state: ResMut<NextState<CharacterMenuState>>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if interaction != &Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button_action {
+            CharacterMenuButtonAction::Member(index) => {
+                if member.0 != *index {
+                    member.0 = *index;
+                }
+            },
+            CharacterMenuButtonAction::Tab(tab) => {
+                next_tab.set(*tab);
+            },
+            CharacterMenuButtonAction::Close => {
+                next_character_menu_state.set(CharacterMenuState::Closed);
+            },
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// BUTTON STYLING
+
+// The selected member and tab are marked UiSelected, so they stay highlighted
+fn mark_selected_buttons(
+    button_query: Query<(Entity, &CharacterMenuButtonAction, Has<UiSelected>)>,
+    tab: Res<State<CharacterMenuTab>>,
+    member: Res<CharacterMenuMember>,
+    mut commands: Commands
+) {
+    for (button, menu_button_action, marked) in &button_query {
+        let selected = match menu_button_action {
+            CharacterMenuButtonAction::Member(index) => *index == member.0,
+            CharacterMenuButtonAction::Tab(t) => t == tab.get(),
+            CharacterMenuButtonAction::Close => false,
+        };
+
+        if selected && !marked {
+            commands.entity(button).insert(UiSelected);
+        } else if !selected && marked {
+            commands.entity(button).remove::<UiSelected>();
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn tab_label(tab: CharacterMenuTab) -> UiString {
+    match tab {
+        CharacterMenuTab::Stats => UiString::key("character.tab_stats"),
+        CharacterMenuTab::Equipment => UiString::key("character.tab_equipment"),
+        CharacterMenuTab::Inventory => UiString::key("character.tab_inventory"),
+        CharacterMenuTab::Skills => UiString::key("character.tab_skills"),
+    }
+}
+
+// Names, items and skills come from the party data, so are shown as written
+fn tab_lines(tab: CharacterMenuTab, member: &PartyMember) -> Vec<UiString> {
+    let mut lines = vec![UiString::key_with(
+        "character.heading",
+        [member.name.clone(), member.level.to_string(), member.class.clone()]
+    )];
+
+    match tab {
+        CharacterMenuTab::Stats => {
+            let stats = &member.stats;
+            lines.extend([
+                UiString::key_with("character.hp", [stats.hp, stats.max_hp]),
+                UiString::key_with("character.mp", [stats.mp, stats.max_mp]),
+                UiString::key_with("character.stat", [UiString::key("stat.strength"), stats.strength.into()]),
+                UiString::key_with("character.stat", [UiString::key("stat.agility"), stats.agility.into()]),
+                UiString::key_with("character.stat", [UiString::key("stat.intellect"), stats.intellect.into()]),
+                UiString::key_with("character.stat", [UiString::key("stat.vitality"), stats.vitality.into()]),
+            ]);
+        },
+        CharacterMenuTab::Equipment => {
+            lines.extend(member.equipment.iter().map(|equipment_slot| {
+                UiString::from(format!("{}: {}", equipment_slot.slot, equipment_slot.item.as_deref().unwrap_or("-")))
+            }));
+        },
+        CharacterMenuTab::Inventory => {
+            if member.inventory.is_empty() {
+                lines.push(UiString::key("character.inventory_empty"));
+            }
+            lines.extend(member.inventory.iter().map(|item| UiString::from(format!("{} x{}", item.name, item.count))));
+        },
+        CharacterMenuTab::Skills => {
+            if member.skills.is_empty() {
+                lines.push(UiString::key("character.no_skills"));
+            }
+            lines.extend(member.skills.iter().map(|skill| UiString::from(format!("{} - {}", skill.name, skill.description))));
+        },
+    }
+
+    lines
+}
@@ -1,36 +1,66 @@
 ///// SPECS
-// - button MainMenuButton nextStates to MainMenu
+// - returning to MainMenu is done through the pause menu (see pause_menu_plugin)
+// - Shop is entered from Explore by a map trigger and left with its Leave button
+// - InGameData.flags holds story flags set by dialogue; they are saved along with the game
+// - InGameData.party holds the party shown in the character menu; a new game takes it from
+//   character creation, or reads it from party::DEFAULT_PARTY_FILEPATH if there wasn't one
+// - a new game starts on the map, cell and facing given by --map, --pos and --facing, if any (see
+//   launch_options)
 //
 
 mod explore_substate;
 mod combat_substate;
-mod shop_substate;
+pub mod party;
+pub mod shop_substate;
 
-use crate::plugins::{
-    manage_state_plugin:: {
-        GameModeState, InGameSubstate,
-        ingame_state_plugin::{
-            explore_substate::setup_exploresubstate,
-        }
+use std::collections::HashMap;
+
+use crate::{
+    launch_options::LaunchOptions,
+    plugins::{
+        camera_plugin::NavigateCamera,
+        manage_state_plugin:: {
+            GameModeState, InGameSubstate, DialogueState,
+            ingame_state_plugin::{
+                explore_substate::{ setup_exploresubstate, spawn_map_npcs, cleanup_exploresubstate },
+                party::{ NewGameParty, PartyMember, DEFAULT_PARTY_FILEPATH, load_party_file },
+                shop_substate::{ setup_shopsubstate, cleanup_shopsubstate, shop_action_system },
+            }
+        },
+        dialogue_plugin::DialoguePlugin,
+        explore_plugin::{
+            ExplorePlugin,
+            map::{ CurrentMap, MapEntered, DEFAULT_MAP_FILEPATH, load_map_file },
+            movement::{ ExplorationMovementData, cardinal_direction_angle },
+        },
+        save_plugin::PendingLoad,
     },
-    explore_plugin::ExplorePlugin
 };
 
 use bevy::prelude::*;
 
 
+// Where a new game starts when no --pos or --facing is given: facing north, on the grid but not
+// yet oriented to it (the first movement snaps to north)
+const NEW_GAME_CAMERA_START: Transform = Transform::from_xyz(0., 5., 20.);
+
+
 pub struct InGameStatePlugin;
 
 impl Plugin for InGameStatePlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<NewGameParty>();
         app.add_systems(OnEnter(GameModeState::InGame), setup);
+        
+        app.add_plugins((ExplorePlugin, DialoguePlugin));
+        app.add_systems(OnEnter(InGameSubstate::Explore), setup_exploresubstate );
         app.add_systems(
             Update,
-            check_enter_mainmenu_system.run_if(in_state(GameModeState::InGame)),
+            spawn_map_npcs
+                .run_if(resource_exists_and_changed::<CurrentMap>)
+                .run_if(in_state(InGameSubstate::Explore))
         );
-        
-        app.add_plugins(ExplorePlugin);
-        app.add_systems(OnEnter(InGameSubstate::Explore), setup_exploresubstate );
+        app.add_systems(OnExit(GameModeState::InGame), cleanup_exploresubstate);
 
         //
         //InGameSubstate::Explore
@@ -38,39 +68,100 @@ impl Plugin for InGameStatePlugin {
         //InGameSubstate::Combat
         //
         //InGameSubstate::Shop
+        app.add_systems(OnEnter(InGameSubstate::Shop), setup_shopsubstate);
+        app.add_systems(OnExit(InGameSubstate::Shop), cleanup_shopsubstate);
+        app.add_systems(
+            Update,
+            shop_action_system
+                .run_if(in_state(InGameSubstate::Shop))
+                .run_if(in_state(DialogueState::Closed))
+        );
         //
     }
 }
 
 
-#[allow(dead_code)]
 #[derive(Resource)]
-struct InGameData {
-    name: String
+pub struct InGameData {
+    pub name: String,
+    // story flags, set by dialogue and checked by dialogue conditions
+    pub flags: HashMap<String, String>,
+    pub party: Vec<PartyMember>
 }
 
 #[derive(Component)]
 struct InGameRootNode;
 
 
+// When a save is about to be loaded, its map is loaded here and the rest of it is applied once
+// Explore is entered (see save_plugin::apply_pending_load). Otherwise this is a new game, which
+// enters the default map (or the one given by --map), with the movement state of any game played
+// before it dropped.
 fn setup(
     mut commands: Commands,
+    pending_load: Res<PendingLoad>,
+    mut new_game_party: ResMut<NewGameParty>,
+    launch_options: Res<LaunchOptions>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    mut camera_query: Query<&mut Transform, With<NavigateCamera>>,
+    mut map_entered: MessageWriter<MapEntered>,
 ) {
-    commands.insert_resource(initialize_ingame_data());
-}
+    // a loaded save replaces this party with its own (see save_plugin::apply_pending_load)
+    let party = match (new_game_party.0.take(), load_party_file(DEFAULT_PARTY_FILEPATH)) {
+        (Some(created), _) => created,
+        (None, Ok(p)) => p.members,
+        (None, Err(e)) => {
+            error!("failure loading party: {}", e);
+            Vec::new()
+        }
+    };
+    commands.insert_resource(initialize_ingame_data(party));
 
-fn initialize_ingame_data() -> InGameData {
-    InGameData {
-        name: String::from("placeholder character name")
+    let map_filepath = match (&pending_load.0, &launch_options.map) {
+        (Some(save), _) => save.map_filepath.as_str(),
+        (None, Some(launch_map)) => launch_map.as_str(),
+        (None, None) => DEFAULT_MAP_FILEPATH,
+    };
+    let map = match load_map_file(map_filepath) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("failure loading map, falling back to default: {}", e);
+            load_map_file(DEFAULT_MAP_FILEPATH).unwrap_or_else(|e| panic!("{}", e))
+        }
+    };
+
+    if pending_load.0.is_none() {
+        movement_data.current_movement_timer = None;
+        movement_data.input_buffer.clear();
+        movement_data.held_movement = None;
+        movement_data.free_looking = false;
+        movement_data.moving_continuously = false;
+        movement_data.current_movement_command = None;
+        movement_data.command_queue.clear();
+        movement_data.cardinal_facing = launch_options.facing;
+        movement_data.oriented_to_cardinal_directions = launch_options.facing.is_some();
+        if let Ok(mut camera_transform) = camera_query.single_mut() {
+            *camera_transform = NEW_GAME_CAMERA_START;
+            if let Some(cell) = launch_options.pos {
+                camera_transform.translation = map.definition.grid.cell_to_world(cell, camera_transform.translation.y);
+            }
+            if let Some(facing) = launch_options.facing {
+                camera_transform.rotation = Quat::from_rotation_y(cardinal_direction_angle(facing));
+            }
+        }
+        map_entered.write(MapEntered { filepath: map.filepath.clone() });
     }
+    commands.insert_resource(map);
 }
 
-fn check_enter_mainmenu_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut next_state: ResMut<NextState<GameModeState>>,
-) {
-    if keyboard_input.pressed(KeyCode::Escape) {
-        next_state.set(GameModeState::MainMenu);
+// The game is named after the party's leader
+fn initialize_ingame_data(party: Vec<PartyMember>) -> InGameData {
+    InGameData {
+        name: party.first()
+            .map(|leader| leader.name.clone())
+            .unwrap_or_else(|| String::from("placeholder character name")),
+        flags: HashMap::new(),
+        party
     }
 }
 
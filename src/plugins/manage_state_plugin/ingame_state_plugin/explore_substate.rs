@@ -5,17 +5,20 @@ use bevy::{
     prelude::{
         Component, Query, Entity, With, Commands, Res, ResMut, Assets, Resource,
         AssetServer, Handle, Image,
-        StandardMaterial, Plane3d, Cuboid, Vec3, Vec2, Color, Transform, 
+        StandardMaterial, Plane3d, Cuboid, Capsule3d, Vec3, Vec2, Color, Transform, 
         Mesh, Mesh3d, MeshMaterial3d,
         error
     },
-    color::palettes::css::RED,
+    color::palettes::css::{ RED, TAN },
     light::PointLight,
     image::CompressedImageFormats,
     core_pipeline::Skybox,
 };
 
-use crate::plugins::camera_plugin::NavigateCamera;
+use crate::plugins::{
+    camera_plugin::NavigateCamera,
+    explore_plugin::map::{ CurrentMap, Npc },
+};
 
 // note - my GPU supports BC KTX2 textures - will need to design system for modular textures based
 // on user GPU
@@ -47,18 +50,26 @@ struct Cubemap {
 }
 
 
+// Everything spawned into the explore scene. The scene is kept while in other InGame substates
+// (e.g. Shop), so returning to Explore puts the player back where they were.
 #[derive(Component)]
-struct ExploreRootNode;
+pub struct ExploreRootNode;
 
 
 pub fn setup_exploresubstate(
     camera_query: Query<Entity, With<NavigateCamera>>,
+    explore_rootnode_query: Query<Entity, With<ExploreRootNode>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     asset_server: Res<AssetServer>
 ) {
 
+    // scene already exists - we are returning to Explore, not entering the game
+    if !explore_rootnode_query.is_empty() {
+        return;
+    }
+
     let nav_cam = match camera_query.single() {
         Ok(c) => c,
         Err(_) => {
@@ -81,40 +92,72 @@ pub fn setup_exploresubstate(
     let cube_position = Transform::from_xyz(0., 5., 0.);
 
     commands.spawn((
+        ExploreRootNode,
         Mesh3d(meshes.add(plane)),
         MeshMaterial3d(materials.add(plane_color)),
         Transform::from_xyz(0., 0., 0.)
     ));
     commands.spawn((
+        ExploreRootNode,
         Mesh3d(meshes.add(cube)),
         MeshMaterial3d(materials.add(cube_color)),
         cube_position
     ));
     commands.spawn((
+        ExploreRootNode,
         PointLight {
             shadows_enabled: true,
             ..PointLight::default()
         },
         Transform::from_xyz(0., 10., 0.)
     ));
-    // Place camera
-    commands.entity(nav_cam).insert((
-        Transform::from_xyz(0., 5., 20.,).looking_at(cube_position.translation, Vec3::Y),
+    // The camera itself is placed when the game starts (see ingame_state_plugin::setup)
+    commands.entity(nav_cam).insert(
         Skybox {
             image: skybox_handle.clone(),
             brightness: 1000.0,
             ..Skybox::default()
         }
-    ));
+    );
 
 }
 
 
 
-// pub fn run_exploresubstate() {
-//
-// }
-//
-// pub fn cleanup_exploresubstate() {
-//
-// }
+/// Respawn the NPCs whenever CurrentMap is replaced (starting a game, loading a save on another map)
+pub fn spawn_map_npcs(
+    npc_query: Query<Entity, With<Npc>>,
+    current_map: Res<CurrentMap>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for npc in &npc_query {
+        commands.entity(npc).despawn();
+    }
+
+    let npc_mesh = meshes.add(Capsule3d::new(1., 3.));
+    let npc_material = materials.add(Color::from(TAN));
+    for map_npc in &current_map.definition.npcs {
+        commands.spawn((
+            ExploreRootNode,
+            Npc {
+                name: map_npc.name.clone(),
+                cell: map_npc.cell,
+                dialogue: map_npc.dialogue.clone()
+            },
+            Mesh3d(npc_mesh.clone()),
+            MeshMaterial3d(npc_material.clone()),
+            Transform::from_translation(current_map.definition.grid.cell_to_world(map_npc.cell, 2.5))
+        ));
+    }
+}
+
+pub fn cleanup_exploresubstate(
+    query: Query<Entity, With<ExploreRootNode>>,
+    mut commands: Commands
+) {
+    for explore_rootnode in &query {
+        commands.entity(explore_rootnode).despawn();
+    }
+}
@@ -0,0 +1,79 @@
+/// This file defines the player's party: the characters shown in the character menu, each with
+/// their own stats, equipment, inventory and skills.
+///
+/// A new game starts with the party made in character creation (NewGameParty), or, when started
+/// without going through it (e.g. --state ingame), the party read from DEFAULT_PARTY_FILEPATH.
+use std::fs;
+use bevy::prelude::{ Reflect, Resource };
+
+use crate::reflect_ron::from_ron_str;
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+pub const DEFAULT_PARTY_FILEPATH: &str = "config/party/default_party.ron";
+
+#[derive(Reflect, Debug, Clone)]
+pub struct Party {
+    pub members: Vec<PartyMember>
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct PartyMember {
+    pub name: String,
+    pub class: String,
+    // the name of a portrait from character creation
+    #[reflect(default)]
+    pub portrait: String,
+    pub level: u32,
+    pub stats: Stats,
+    #[reflect(default)]
+    pub equipment: Vec<EquipmentSlot>,
+    #[reflect(default)]
+    pub inventory: Vec<InventoryItem>,
+    #[reflect(default)]
+    pub skills: Vec<Skill>
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct Stats {
+    pub hp: u32,
+    pub max_hp: u32,
+    pub mp: u32,
+    pub max_mp: u32,
+    pub strength: u32,
+    pub agility: u32,
+    pub intellect: u32,
+    pub vitality: u32
+}
+
+// item is None when nothing is equipped in the slot
+#[derive(Reflect, Debug, Clone)]
+pub struct EquipmentSlot {
+    pub slot: String,
+    #[reflect(default)]
+    pub item: Option<String>
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct InventoryItem {
+    pub name: String,
+    pub count: u32
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct Skill {
+    pub name: String,
+    pub description: String
+}
+
+
+/// Set by character creation, and taken by the next new game
+#[derive(Resource, Default)]
+pub struct NewGameParty(pub Option<Vec<PartyMember>>);
+
+
+pub fn load_party_file(filepath: &str) -> Result<Party, String> {
+    let party_ron_str = fs::read_to_string(filepath).map_err(|e| format!("{}: {}", filepath, e))?;
+    from_ron_str(&party_ron_str).map_err(|e| format!("{}: {}", filepath, e))
+}
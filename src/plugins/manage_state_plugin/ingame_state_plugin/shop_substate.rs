@@ -0,0 +1,91 @@
+// THIS FILE SHOULD ONLY CONTAIN code pertaining to the Shop substate - entered from Explore by
+// stepping onto a Shop tile trigger (see explore_plugin::map::check_tile_triggers), and left back
+// to Explore with the Leave button (or the Back action).
+//
+// There is nothing to buy yet; the shop screen shows the shop's name and plays its greeting
+// dialogue (if it has one).
+
+use bevy::prelude::*;
+
+use crate::plugins::{
+    dialogue_plugin::StartDialogue,
+    manage_state_plugin::InGameSubstate,
+    ui_plugin::{ focus::BackButton, localization::UiString, theme::UiTheme, widgets },
+};
+
+
+/// The shop being visited. Inserted by whatever sends the player into InGameSubstate::Shop.
+#[derive(Resource, Debug)]
+pub struct ShopVisit {
+    pub name: String,
+    pub greeting_dialogue: Option<String>
+}
+
+#[derive(Component)]
+pub struct ShopRootNode;
+
+#[derive(Component)]
+pub enum ShopButtonAction {
+    Leave
+}
+
+
+pub fn setup_shopsubstate(
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    shop_visit: Option<Res<ShopVisit>>,
+    mut start_dialogue: MessageWriter<StartDialogue>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let ui_camera = match camera_query.single() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let shop_name = match shop_visit.as_ref() {
+        Some(s) => UiString::from(&s.name),
+        None => UiString::key("shop.default_name"),
+    };
+
+    commands.spawn((
+        ShopRootNode,
+        widgets::screen_root(&theme, ui_camera),
+        children![
+            widgets::title_text(&theme, shop_name),
+            (
+                ShopButtonAction::Leave,
+                BackButton,
+                widgets::button(&theme, UiString::key("shop.leave"))
+            )
+        ]
+    ));
+
+    if let Some(greeting_dialogue) = shop_visit.and_then(|s| s.greeting_dialogue.clone()) {
+        start_dialogue.write(StartDialogue { filepath: greeting_dialogue });
+    }
+}
+
+pub fn cleanup_shopsubstate(
+    query: Query<Entity, With<ShopRootNode>>,
+    mut commands: Commands
+) {
+    for shop_rootnode in &query {
+        commands.entity(shop_rootnode).despawn();
+    }
+    commands.remove_resource::<ShopVisit>();
+}
+
+pub fn shop_action_system(
+    interaction_query: Query<
+        (&Interaction, &ShopButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut next_substate: ResMut<NextState<InGameSubstate>>,
+) {
+    for (interaction, shop_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match shop_button_action {
+                ShopButtonAction::Leave => { next_substate.set(InGameSubstate::Explore); }
+            }
+        }
+    }
+}
@@ -1,32 +1,108 @@
 ///// SPECS
-// - button StartButton nextStates to MainMenu
+// - plays the splash sequence read from SPLASH_FILEPATH: each card (a logo image or a line of text)
+//   fades in, holds, then fades out
+// - any key, gamepad button or mouse click skips the rest of the sequence
+// - after the sequence the title and a "press any key" prompt are shown; any input then nextStates
+//   to MainMenu
+// - setup_intro_screen also runs in Startup, once the UI camera exists (the initial state's OnEnter
+//   runs before it does); the screen is only spawned once
 
-use crate::plugins::{ manage_state_plugin::GameModeState, camera_plugin::UiCamera };
+use std::fs;
+use crate::{
+    plugins::{
+        manage_state_plugin::GameModeState,
+        camera_plugin::{ UiCamera, setup_ui_camera },
+        ui_plugin::{ theme::UiTheme, widgets },
+    },
+    reflect_ron::from_ron_str,
+};
 use bevy::prelude::*;
 
 
+/////////////////////////////////////////
+// CONFIGURABLES
+const SPLASH_FILEPATH: &str = "config/splash.ron";
+
+#[derive(Reflect, Resource, Debug, Clone)]
+pub struct SplashSequence {
+    pub cards: Vec<SplashCard>,
+    pub title: String,
+    pub prompt: String
+}
+
+// Times in seconds. The fades are part of duration, not added to it.
+#[derive(Reflect, Debug, Clone)]
+pub struct SplashCard {
+    pub content: SplashContent,
+    pub duration: f32,
+    pub fade_in: f32,
+    pub fade_out: f32
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub enum SplashContent {
+    /// Path relative to the assets folder
+    Image(String),
+    Text(String)
+}
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
 pub struct IntroScreenPlugin;
 
 impl Plugin for IntroScreenPlugin {
     fn build(&self, app: &mut App) {
         info!("Running IntroScreenPlugin app::build");
+        // inserted here, as setup_intro_screen can run before any startup system
+        app.insert_resource(load_splash_sequence());
         app.add_systems(OnEnter(GameModeState::IntroScreen), setup_intro_screen);
+        app.add_systems(Startup, setup_intro_screen.after(setup_ui_camera));
         app.add_systems(OnExit(GameModeState::IntroScreen), cleanup_intro_screen);
         app.add_systems(
             Update,
-            run_intro_screen.run_if(in_state(GameModeState::IntroScreen)),
+            (
+                run_intro_screen,
+                refresh_intro_content,
+                fade_splash_card
+            ).chain().run_if(in_state(GameModeState::IntroScreen)),
         );
     }
 }
 
 
+/////////////////////////////////////////
+// NODE STRUCTURE
+
 #[derive(Component)]
 pub struct IntroScreenRootNode;
 
-// TEST: CALLING THIS IN STARTUP IN MAIN.RS
+// Holds the current card, or the title and prompt
+#[derive(Component)]
+struct IntroContentNode;
+
+#[derive(Component)]
+struct SplashCardNode;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum IntroPhase {
+    Splash(usize),
+    Title
+}
+
+#[derive(Resource)]
+struct IntroPlayback {
+    phase: IntroPhase,
+    // seconds into the current card
+    elapsed: f32
+}
+
 pub fn setup_intro_screen(
     camera_query: Query<Entity, With<UiCamera>>,
     root_query: Query<Entity, With<IntroScreenRootNode>>,
+    splash: Res<SplashSequence>,
+    theme: Res<UiTheme>,
     mut commands: Commands
 ) {
 
@@ -38,38 +114,21 @@ pub fn setup_intro_screen(
         Ok(c) => c,
         Err(_) => return
     };
-    
+
+    commands.insert_resource(IntroPlayback {
+        phase: match splash.cards.is_empty() {
+            true => IntroPhase::Title,
+            false => IntroPhase::Splash(0),
+        },
+        elapsed: 0.
+    });
+
     commands.spawn((
-        // root container to center the button within
         IntroScreenRootNode,
-        Node {
-            width: Val::Percent(100.),
-            height: Val::Percent(100.),
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            ..default()
-        },
-        BackgroundColor(Color::srgb(0.0, 0.0, 0.0)),
-        UiTargetCamera(ui_camera),
+        widgets::screen_root(&theme, ui_camera),
         children![(
-            Button, 
-            Node {
-                width: Val::Px(150.),
-                height: Val::Px(65.),
-                // horizontally + vertically center child text
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            BackgroundColor(NORMAL_BUTTON.into()),
-            children![(
-                Text::new("Start"),
-                TextFont {
-                    font_size: 40.0,
-                    ..default()
-                },
-                TextColor(Color::srgb(0.9, 0.9, 0.9))
-            )]
+            IntroContentNode,
+            widgets::column(&theme)
         )]
     ));
 
@@ -78,8 +137,10 @@ pub fn setup_intro_screen(
 
 fn cleanup_intro_screen(
     query: Query<Entity, With<IntroScreenRootNode>>,
-    mut commands: Commands, 
+    mut commands: Commands,
 ) {
+    commands.remove_resource::<IntroPlayback>();
+
     let introscreen_root_node = match query.single() {
         Ok(n) => n,
         Err(_) => return,
@@ -90,34 +151,149 @@ fn cleanup_intro_screen(
         .despawn();
 }
 
+// Rebuilds the content whenever the phase moves on. The new card starts fully visible, but
+// fade_splash_card runs straight after in the same frame.
+fn refresh_intro_content(
+    playback: Option<Res<IntroPlayback>>,
+    content_query: Query<(Entity, Has<Children>), With<IntroContentNode>>,
+    splash: Res<SplashSequence>,
+    theme: Res<UiTheme>,
+    asset_server: Res<AssetServer>,
+    mut shown_phase: Local<Option<IntroPhase>>,
+    mut commands: Commands
+) {
+    let playback = match playback {
+        Some(p) => p,
+        None => return,
+    };
+    let (content_node, has_content) = match content_query.single() {
+        Ok(n) => n,
+        Err(_) => return,
+    };
+    // a fresh content node (the screen was entered again) has nothing shown yet
+    if has_content && *shown_phase == Some(playback.phase) {
+        return;
+    }
+    *shown_phase = Some(playback.phase);
 
-/////////////////////////////////////////
-// BUTTON STYLES
+    commands.entity(content_node).despawn_related::<Children>();
+    commands.entity(content_node).with_children(|content| {
+        match playback.phase {
+            IntroPhase::Splash(index) => {
+                let card = &splash.cards[index];
+                match &card.content {
+                    SplashContent::Image(path) => {
+                        content.spawn((SplashCardNode, ImageNode::new(asset_server.load(path))));
+                    }
+                    SplashContent::Text(text) => {
+                        content.spawn((SplashCardNode, widgets::title_text(&theme, text)));
+                    }
+                }
+            }
+            IntroPhase::Title => {
+                content.spawn(widgets::title_text(&theme, &splash.title));
+                content.spawn(widgets::body_text(&theme, &splash.prompt));
+            }
+        }
+    });
+}
 
-const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
-const PRESSED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
-const HOVERED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+// Cards fade in and out over their fade_in and fade_out times
+fn fade_splash_card(
+    playback: Option<Res<IntroPlayback>>,
+    splash: Res<SplashSequence>,
+    theme: Res<UiTheme>,
+    mut card_query: Query<(Option<&mut TextColor>, Option<&mut ImageNode>), With<SplashCardNode>>
+) {
+    let playback = match playback {
+        Some(p) => p,
+        None => return,
+    };
+    let card = match playback.phase {
+        IntroPhase::Splash(index) => &splash.cards[index],
+        IntroPhase::Title => return,
+    };
+
+    let fade_in = match card.fade_in > 0. {
+        true => (playback.elapsed / card.fade_in).min(1.),
+        false => 1.,
+    };
+    let fade_out = match card.fade_out > 0. {
+        true => ((card.duration - playback.elapsed) / card.fade_out).clamp(0., 1.),
+        false => 1.,
+    };
+    let alpha = fade_in.min(fade_out);
+
+    for (text_color, image_node) in &mut card_query {
+        if let Some(mut text_color) = text_color {
+            text_color.0 = theme.colors.title_text.with_alpha(alpha);
+        }
+        if let Some(mut image_node) = image_node {
+            image_node.color = Color::WHITE.with_alpha(alpha);
+        }
+    }
+}
+
+/////////////////////////////////////////
+// BUTTON FUNCTIONALITY
 
 fn run_intro_screen(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    splash: Res<SplashSequence>,
+    playback: Option<ResMut<IntroPlayback>>,
     mut next_state: ResMut<NextState<GameModeState>>,
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<Button>),
-    >,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *color = PRESSED_BUTTON.into();
-                next_state.set(GameModeState::MainMenu);
-            }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
+    let mut playback = match playback {
+        Some(p) => p,
+        None => return,
+    };
+
+    let any_input = keyboard_input.get_just_pressed().next().is_some()
+        || mouse_input.get_just_pressed().next().is_some()
+        || gamepads.iter().any(|gamepad| gamepad.get_just_pressed().next().is_some());
+
+    match playback.phase {
+        IntroPhase::Splash(_) if any_input => {
+            playback.phase = IntroPhase::Title;
+        }
+        IntroPhase::Splash(index) => {
+            playback.elapsed += time.delta_secs();
+            if playback.elapsed >= splash.cards[index].duration {
+                playback.elapsed = 0.;
+                playback.phase = match index + 1 < splash.cards.len() {
+                    true => IntroPhase::Splash(index + 1),
+                    false => IntroPhase::Title,
+                };
             }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
+        }
+        IntroPhase::Title => {
+            if any_input {
+                next_state.set(GameModeState::MainMenu);
             }
         }
     }
 }
 
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn load_splash_sequence() -> SplashSequence {
+    let read = fs::read_to_string(SPLASH_FILEPATH)
+        .map_err(|e| e.to_string())
+        .and_then(|ron_str| from_ron_str(&ron_str));
+    match read {
+        Ok(s) => s,
+        Err(e) => {
+            error!("failure loading {}, skipping the splash sequence: {}", SPLASH_FILEPATH, e);
+            SplashSequence {
+                cards: Vec::new(),
+                title: String::from("dcrawler"),
+                prompt: String::from("Press any key")
+            }
+        }
+    }
+}
@@ -1,21 +1,24 @@
 ///// SPECS
-// - render savefile if exists (TBD)
-// - button ReturnButton nextStates to MainMenu
-// - button LoadGameButton nextStates (for now) to InGame
-// - button DeleteSaveButton deletes the savefile (functionality TBD)
-//
-// TODO: SAVELOAD SYSTEMS create and read off text files?
+// - render a row per savefile (autosaves and quicksave labelled as such), most recent first;
+//   clicking a row selects it; the most recent row has the initial focus
+// - button ReturnButton nextStates to MainMenu (also the Back action)
+// - button LoadGameButton loads the selected savefile and nextStates to InGame
+// - button DeleteSaveButton deletes the selected savefile
 
-use crate::plugins::manage_state_plugin::GameModeState;
-use bevy::{ecs::spawn::SpawnRelatedBundle, prelude::*};
-
-
-/////////////////////////////////////////
-// CONFIGURABLES
-// - BUTTON COLORS
-const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
-const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
-const HOVERED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+use crate::plugins::{
+    manage_state_plugin::GameModeState,
+    save_plugin::{
+        SaveData, SaveSlot, SavesDir, PendingLoad, list_saves, read_save, delete_save,
+        thumbnail::read_thumbnail,
+    },
+    ui_plugin::{
+        focus::{ BackButton, InitialFocus },
+        localization::UiString,
+        theme::UiTheme,
+        widgets::{ self, UiSelected },
+    },
+};
+use bevy::prelude::*;
 
 
 /////////////////////////////////////////
@@ -25,11 +28,16 @@ pub struct LoadGameMenuPlugin;
 
 impl Plugin for LoadGameMenuPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedSave>();
         app.add_systems(OnEnter(GameModeState::LoadGameMenu), setup_loadgamemenu);
         app.add_systems(OnExit(GameModeState::LoadGameMenu), cleanup_loadgamemenu);
         app.add_systems(
             Update,
-            (style_buttons, loadgamemenu_action_system).run_if(in_state(GameModeState::LoadGameMenu))
+            (
+                select_save_row_system,
+                loadgamemenu_action_system,
+                mark_selected_save_row
+            ).chain().run_if(in_state(GameModeState::LoadGameMenu))
         );
     }
 }
@@ -41,8 +49,18 @@ impl Plugin for LoadGameMenuPlugin {
 #[derive(Component)]
 struct LoadGameMenuRootNode;
 
+#[derive(Component)]
+struct SaveRowSlot(SaveSlot);
+
+#[derive(Resource, Default)]
+struct SelectedSave(Option<SaveSlot>);
+
 fn setup_loadgamemenu(
     camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    saves_dir: Res<SavesDir>,
+    mut selected_save: ResMut<SelectedSave>,
+    mut images: ResMut<Assets<Image>>,
+    theme: Res<UiTheme>,
     mut commands: Commands
 ) {
     let ui_camera = match camera_query.single() {
@@ -50,89 +68,61 @@ fn setup_loadgamemenu(
         Err(_) => return,
     };
 
+    let saves = list_saves(&saves_dir);
+    selected_save.0 = saves.first().map(|save| save.slot);
+
     commands.spawn((
         LoadGameMenuRootNode,
-        Node {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            align_items: AlignItems::Center,
-            justify_content: JustifyContent::Center,
-            ..default()
-        },
-        UiTargetCamera(ui_camera),
-        children![
-            (
-                Node {
-                    width: Val::Percent(50.0),
-                    height: Val::Percent(10.0),
-                    align_items: AlignItems::Center,
-                    justify_content: JustifyContent::Start,
-                    flex_direction: FlexDirection::Row,
-                    ..default()
-                },
-                children![
-                    (
-                        Node {
-                            height: Val::Percent(100.0),
-                            aspect_ratio: Some(1.),
-                            ..default()
-                        },
-                        BackgroundColor(Color::srgb(0.9, 0.05, 0.05))
-                    ),
-                    (
-                        Node {
-                            height: Val::Percent(100.0),
-                            margin: UiRect {
-                                left: Val::Px(20.),
-                                ..default()
-                            },
-                            ..default()
-                        },
-                        children![(
-                            Text::new("Placeholder save data"),
-                            TextFont {
-                                font_size: 14.0,
-                                ..default()
-                            },
-                            TextColor(Color::srgb(0.9, 0.9, 0.9))
-                        )]
-
-                    )
-                ]
-            ),
-            (
-                LoadGameMenuButtonAction::Load,
-                generate_placeholder_button("Load")
-            ),
-            (
-                LoadGameMenuButtonAction::Erase,
-                generate_placeholder_button("Erase")
-            ),
-            (
+        widgets::screen_root(&theme, ui_camera),
+        children![(
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.),
+                right: Val::Px(14.),
+                ..default()
+            },
+            children![(
                 LoadGameMenuButtonAction::Return,
-                Button,
-                Node {
-                    width: Val::Px(150.),
-                    height: Val::Px(65.),
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    position_type: PositionType::Absolute,
-                    top: Val::Px(20.),
-                    right: Val::Px(14.),
-                    ..default()
-                },
-                BackgroundColor(NORMAL_BUTTON.into()),
-                children![(
-                    Text::new("Return"),
-                    TextFont {
-                        font_size: 20.0,
-                        ..default()
-                    },
-                    TextColor(Color::srgb(0.9, 0.9, 0.9))
-                )]
-            ),
-        ]
-    ));
+                BackButton,
+                widgets::button(&theme, UiString::key("load.return"))
+            )]
+        )]
+    )).with_children(|parent| {
+        // savefile list
+        parent.spawn(Node {
+            width: Val::Percent(50.0),
+            ..widgets::list(&theme)
+        }).with_children(|list| {
+            if saves.is_empty() {
+                list.spawn(widgets::small_text(&theme, UiString::key("load.no_saves")));
+            }
+            for (index, save) in saves.iter().enumerate() {
+                let thumbnail = read_thumbnail(&save.slot.thumbnail_filepath(&saves_dir))
+                    .map(|image| images.add(image));
+                let mut row = list.spawn((
+                    SaveRowSlot(save.slot),
+                    generate_save_row(&theme, save, thumbnail)
+                ));
+                if index == 0 {
+                    row.insert(InitialFocus);
+                }
+            }
+        });
+
+        parent.spawn((
+            widgets::button_row(&theme),
+            children![
+                (
+                    LoadGameMenuButtonAction::Load,
+                    widgets::button(&theme, UiString::key("load.load"))
+                ),
+                (
+                    LoadGameMenuButtonAction::Erase,
+                    widgets::button(&theme, UiString::key("load.erase"))
+                ),
+            ]
+        ));
+    });
 } 
 
 fn cleanup_loadgamemenu(
@@ -160,21 +150,62 @@ enum LoadGameMenuButtonAction {
     Return
 }
 
+fn select_save_row_system(
+    interaction_query: Query<
+        (&Interaction, &SaveRowSlot),
+        (Changed<Interaction>, With<Button>)
+    >,
+    mut selected_save: ResMut<SelectedSave>
+) {
+    for (interaction, save_row_slot) in &interaction_query {
+        if interaction == &Interaction::Pressed {
+            selected_save.0 = Some(save_row_slot.0);
+        }
+    }
+}
+
 fn loadgamemenu_action_system(
     interaction_query: Query<
         (&Interaction, &LoadGameMenuButtonAction),
         (Changed<Interaction>, With<Button>)
     >,
+    save_row_query: Query<(Entity, &SaveRowSlot)>,
+    saves_dir: Res<SavesDir>,
+    mut selected_save: ResMut<SelectedSave>,
+    mut pending_load: ResMut<PendingLoad>,
+    mut commands: Commands,
     mut next_state: ResMut<NextState<GameModeState>>
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if interaction == &Interaction::Pressed {
             match menu_button_action {
                 LoadGameMenuButtonAction::Load => {
-                    next_state.set(GameModeState::InGame);
+                    let slot = match selected_save.0 {
+                        Some(s) => s,
+                        None => continue,
+                    };
+                    match read_save(&saves_dir, &slot) {
+                        Ok(save) => {
+                            pending_load.0 = Some(save);
+                            next_state.set(GameModeState::InGame);
+                        },
+                        Err(e) => error!("failure reading {}: {}", slot.label(), e)
+                    }
                 }
                 LoadGameMenuButtonAction::Erase => {
-                    next_state.set(GameModeState::IntroScreen);
+                    let slot = match selected_save.0.take() {
+                        Some(s) => s,
+                        None => continue,
+                    };
+                    if let Err(e) = delete_save(&saves_dir, &slot) {
+                        error!("failure erasing {}: {}", slot.label(), e);
+                        continue;
+                    }
+                    for (row, save_row_slot) in &save_row_query {
+                        if save_row_slot.0 == slot {
+                            commands.entity(row).despawn();
+                        }
+                    }
                 }
                 LoadGameMenuButtonAction::Return => {
                     next_state.set(GameModeState::MainMenu);
@@ -188,26 +219,18 @@ fn loadgamemenu_action_system(
 /////////////////////////////////////////
 // BUTTON STYLING
 
-fn style_buttons(
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor, &mut BorderColor),
-        (Changed<Interaction>, With<Button>)
-    >
+// The selected save row is marked UiSelected, so it stays highlighted
+fn mark_selected_save_row(
+    selected_save: Res<SelectedSave>,
+    row_query: Query<(Entity, &SaveRowSlot, Has<UiSelected>)>,
+    mut commands: Commands
 ) {
-    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *background_color = PRESSED_BUTTON.into();
-                border_color.set_all(bevy::color::palettes::basic::RED);
-            }
-            Interaction::Hovered => {
-                *background_color = HOVERED_BUTTON.into();
-                border_color.set_all(bevy::color::palettes::basic::RED);
-            }
-            Interaction::None => {
-                *background_color = NORMAL_BUTTON.into();
-                border_color.set_all(Color::BLACK);
-            }
+    for (row, save_row_slot, marked) in &row_query {
+        let selected = selected_save.0 == Some(save_row_slot.0);
+        if selected && !marked {
+            commands.entity(row).insert(UiSelected);
+        } else if !selected && marked {
+            commands.entity(row).remove::<UiSelected>();
         }
     }
 }
@@ -216,25 +239,56 @@ fn style_buttons(
 /////////////////////////////////////////
 // HELPER FUNCTIONS
 
-fn generate_placeholder_button(text: &str) -> (Button, Node, BackgroundColor, SpawnRelatedBundle<ChildOf, Spawn<(Text, TextFont, TextColor)>>) {
+// The square on the left of each row shows the save's thumbnail, or a placeholder colour if it
+// has none - an ImageNode without an image would draw white over the colour
+fn generate_save_row(theme: &UiTheme, save: &SaveData, thumbnail: Option<Handle<Image>>) -> impl Bundle {
+    let placeholder = theme.colors.placeholder;
     (
-        Button,
-        Node {
-            width: Val::Px(150.),
-            height: Val::Px(65.),
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            ..default()
-        },
-        BackgroundColor(NORMAL_BUTTON.into()),
-        children![(
-            Text::new(text),
-            TextFont {
-                font_size: 20.0,
-                ..default()
-            },
-            TextColor(Color::srgb(0.9, 0.9, 0.9))
-        )]
+        widgets::list_row(theme),
+        Children::spawn((
+            SpawnWith(move |row: &mut ChildSpawner| {
+                let mut square = row.spawn((
+                    Node {
+                        height: Val::Percent(100.0),
+                        aspect_ratio: Some(1.),
+                        ..default()
+                    },
+                    BackgroundColor(placeholder)
+                ));
+                if let Some(image) = thumbnail {
+                    square.insert(ImageNode::new(image));
+                }
+            }),
+            Spawn((
+                Node {
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    margin: UiRect {
+                        left: Val::Px(20.),
+                        ..default()
+                    },
+                    ..default()
+                },
+                children![widgets::small_text(theme, save_row_text(save))]
+            ))
+        ))
     )
 }
 
+// "Slot 2 - Aldric - test.ron"
+fn save_row_text(save: &SaveData) -> UiString {
+    match save.slot {
+        SaveSlot::Manual(n) => UiString::key_with(
+            "load.row_manual",
+            [(n + 1).to_string(), save.character_name.clone(), save.map_name.clone()]
+        ),
+        SaveSlot::Autosave(n) => UiString::key_with(
+            "load.row_autosave",
+            [(n + 1).to_string(), save.character_name.clone(), save.map_name.clone()]
+        ),
+        SaveSlot::Quicksave => UiString::key_with(
+            "load.row_quicksave",
+            [save.character_name.clone(), save.map_name.clone()]
+        ),
+    }
+}
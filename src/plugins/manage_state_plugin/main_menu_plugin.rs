@@ -1,22 +1,17 @@
 ///// SPECS
+// - button ContinueButton (only shown if a save exists, and focused first) loads the most recently
+//   written save and nextStates to InGame
 // - button ExitButton exits game
 // - button LoadMenuButton nextStates to LoadGameMenu
-// - button NewGameButton nextStates (for now) to InGame
+// - button NewGameButton nextStates to CharacterCreation
 //
 
-use crate::plugins::manage_state_plugin::GameModeState;
-use bevy::{
-    prelude::*,
-    ecs::spawn::SpawnRelatedBundle, 
+use crate::plugins::{
+    manage_state_plugin::GameModeState,
+    save_plugin::{ PendingLoad, SavesDir, list_saves },
+    ui_plugin::{ focus::InitialFocus, localization::UiString, theme::UiTheme, widgets },
 };
-
-
-/////////////////////////////////////////
-// CONFIGURABLES
-// - BUTTON COLORS
-const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
-const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
-const HOVERED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+use bevy::prelude::*;
 
 
 /////////////////////////////////////////
@@ -30,7 +25,7 @@ impl Plugin for MainMenuPlugin {
         app.add_systems(OnExit(GameModeState::MainMenu), cleanup_mainmenu);
         app.add_systems(
             Update,
-            (style_buttons, main_menu_action_system).run_if(in_state(GameModeState::MainMenu)),
+            main_menu_action_system.run_if(in_state(GameModeState::MainMenu)),
         );
     }
 }
@@ -44,6 +39,8 @@ struct MainMenuRootNode;
 
 fn setup_mainmenu(
     camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    saves_dir: Res<SavesDir>,
+    theme: Res<UiTheme>,
     mut commands: Commands
 ) {
 
@@ -52,32 +49,33 @@ fn setup_mainmenu(
         Err(_) => return,
     };
 
+    let has_saves = !list_saves(&saves_dir).is_empty();
+
     // render a screen
     commands.spawn((
         MainMenuRootNode,
-        Node {
-            width: Val::Percent(100.0),
-            height: Val::Percent(100.0),
-            align_items: AlignItems::Center,
-             justify_content: JustifyContent::Center,
-            ..default()
-        },
-        UiTargetCamera(ui_camera),
-        children![
-            (
-                MainMenuButtonAction::New,
-                generate_main_menu_button("New")
-            ),
-            (
-                MainMenuButtonAction::Load, 
-                generate_main_menu_button("Load")
-            ),
-            (
-                MainMenuButtonAction::Quit,
-                generate_main_menu_button("Quit")
-            )
-        ]
-    ));
+        widgets::screen_root(&theme, ui_camera)
+    )).with_children(|parent| {
+        if has_saves {
+            parent.spawn((
+                MainMenuButtonAction::Continue,
+                InitialFocus,
+                widgets::button(&theme, UiString::key("menu.continue"))
+            ));
+        }
+        parent.spawn((
+            MainMenuButtonAction::New,
+            widgets::button(&theme, UiString::key("menu.new"))
+        ));
+        parent.spawn((
+            MainMenuButtonAction::Load,
+            widgets::button(&theme, UiString::key("menu.load"))
+        ));
+        parent.spawn((
+            MainMenuButtonAction::Quit,
+            widgets::button(&theme, UiString::key("menu.quit"))
+        ));
+    });
 
 }
 
@@ -102,6 +100,7 @@ fn cleanup_mainmenu(
 
 #[derive(Component)]
 enum MainMenuButtonAction {
+    Continue,
     New,
     Load,
     Quit,
@@ -112,14 +111,26 @@ fn main_menu_action_system(
         (&Interaction, &MainMenuButtonAction),
         (Changed<Interaction>, With<Button>),
     >,
+    saves_dir: Res<SavesDir>,
+    mut pending_load: ResMut<PendingLoad>,
     mut next_state: ResMut<NextState<GameModeState>>,
     mut app_exit_events: EventWriter<AppExit>,
 ) {
     for (interaction, menu_button_action) in &interaction_query {
         if interaction == &Interaction::Pressed {
             match menu_button_action {
+                // the saves are listed again here, in case one was written or erased since setup
+                MainMenuButtonAction::Continue => {
+                    match list_saves(&saves_dir).into_iter().next() {
+                        Some(save) => {
+                            pending_load.0 = Some(save);
+                            next_state.set(GameModeState::InGame);
+                        },
+                        None => error!("no save to continue from")
+                    }
+                },
                 MainMenuButtonAction::New => {
-                    next_state.set(GameModeState::InGame);
+                    next_state.set(GameModeState::CharacterCreation);
                 },
                 MainMenuButtonAction::Load => {
                     next_state.set(GameModeState::LoadGameMenu);
@@ -131,57 +142,3 @@ fn main_menu_action_system(
         }
     }
 }
-
-
-/////////////////////////////////////////
-// BUTTON STYLING
-
-fn style_buttons(
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor, &mut BorderColor),
-        (Changed<Interaction>, With<Button>),
-    >,
-) {
-    for (interaction, mut background_color, mut border_color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *background_color = PRESSED_BUTTON.into();
-                border_color.set_all(bevy::color::palettes::basic::RED);
-            }
-            Interaction::Hovered => {
-                *background_color = HOVERED_BUTTON.into();
-                border_color.set_all(bevy::color::palettes::basic::RED);
-            }
-            Interaction::None => {
-                *background_color = NORMAL_BUTTON.into();
-                border_color.set_all(Color::BLACK);
-            }
-        }
-    }
-}
-
-
-/////////////////////////////////////////
-// HELPER FUNCTIONS 
-
-fn generate_main_menu_button(text: &str) -> (Button, Node, BackgroundColor, SpawnRelatedBundle<ChildOf, Spawn<(Text, TextFont, TextColor)>>) {
-    (
-        Button,
-        Node {
-            width: Val::Px(150.),
-            height: Val::Px(65.),
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            ..default()
-        },
-        BackgroundColor(NORMAL_BUTTON.into()),
-        children![(
-            Text::new(text),
-            TextFont {
-                font_size: 40.0,
-                ..default()
-            },
-            TextColor(Color::srgb(0.9, 0.9, 0.9))
-        )]
-    )
-}
@@ -0,0 +1,494 @@
+///// SPECS
+// - "Menu" binding toggles PauseState while InGame; pressing it on a sub-page returns to the main
+//   page instead
+// - while paused, virtual time is paused and the menu is drawn as an overlay over the scene
+// - button ResumeButton nextStates PauseState to Running
+// - button SaveButton writes a manual save
+// - button SettingsButton opens the settings page (autosave options, movement mode and language,
+//   written back to config); its Controls button lists each action's keyboard and gamepad binding
+// - button QuitButton asks for confirmation, then nextStates to MainMenu; Cancel has the initial
+//   focus on the confirmation page
+// - the Back action presses Resume on the main page, and Back/Cancel on the sub-pages
+//
+
+use crate::plugins::{
+    explore_plugin::continuous::MovementMode,
+    manage_state_plugin::{ GameModeState, PauseState },
+    exposed_config_plugin::{ ActionInput, ExposedConfig, update_exposed_config_file },
+    save_plugin::{ SaveRequest, SavesDir, next_manual_slot },
+    ui_plugin::{
+        focus::{ BackButton, InitialFocus },
+        localization::{ UiString, available_languages },
+        theme::UiTheme,
+        widgets,
+    },
+};
+use bevy::prelude::*;
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+// - AUTOSAVE STEP INTERVALS cycled through on the settings page; 0 is off
+const AUTOSAVE_STEP_OPTIONS: [u32; 4] = [0, 25, 50, 100];
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+// Which page of the pause menu is showing. Only exists while paused.
+#[derive(SubStates, Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[source(PauseState = PauseState::Paused)]
+pub(super) enum PauseMenuPage {
+    #[default]
+    Main,
+    Settings,
+    Controls,
+    ConfirmQuit
+}
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<PauseMenuPage>();
+
+        app.add_systems(
+            Update,
+            toggle_pause_system.run_if(in_state(GameModeState::InGame))
+        );
+        app.add_systems(OnEnter(PauseState::Paused), (pause_virtual_time, setup_pausemenu));
+        app.add_systems(OnExit(PauseState::Paused), (unpause_virtual_time, cleanup_pausemenu));
+
+        app.add_systems(OnEnter(PauseMenuPage::Main), setup_main_page);
+        app.add_systems(OnEnter(PauseMenuPage::Settings), setup_settings_page);
+        app.add_systems(OnEnter(PauseMenuPage::Controls), setup_controls_page);
+        app.add_systems(OnEnter(PauseMenuPage::ConfirmQuit), setup_confirmquit_page);
+        app.add_systems(OnExit(PauseMenuPage::Main), cleanup_page);
+        app.add_systems(OnExit(PauseMenuPage::Settings), cleanup_page);
+        app.add_systems(OnExit(PauseMenuPage::Controls), cleanup_page);
+        app.add_systems(OnExit(PauseMenuPage::ConfirmQuit), cleanup_page);
+
+        app.add_systems(
+            Update,
+            (pausemenu_action_system, update_settings_labels)
+                .run_if(in_state(PauseState::Paused))
+        );
+    }
+}
+
+
+/////////////////////////////////////////
+// PAUSING
+
+pub(super) fn toggle_pause_system(
+    mut action_input: ResMut<ActionInput>,
+    pause_state: Res<State<PauseState>>,
+    pause_menu_page: Option<Res<State<PauseMenuPage>>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_pause_menu_page: ResMut<NextState<PauseMenuPage>>,
+) {
+    if !action_input.consume("Menu") {
+        return;
+    }
+
+    match pause_state.get() {
+        PauseState::Running => next_pause_state.set(PauseState::Paused),
+        PauseState::Paused => match pause_menu_page.as_deref().map(State::get) {
+            Some(PauseMenuPage::Main) | None => next_pause_state.set(PauseState::Running),
+            Some(_) => next_pause_menu_page.set(PauseMenuPage::Main),
+        }
+    }
+}
+
+fn pause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn unpause_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+
+/////////////////////////////////////////
+// NODE STRUCTURE
+
+#[derive(Component)]
+struct PauseMenuRootNode;
+
+// Pages are spawned into (and despawned from) this panel as PauseMenuPage changes
+#[derive(Component)]
+struct PauseMenuPanel;
+
+#[derive(Component)]
+struct PauseMenuPageNode;
+
+#[derive(Component)]
+enum SettingsLabel {
+    AutosaveOnMapEnter,
+    AutosaveOnShopExit,
+    AutosaveEveryNSteps,
+    MovementMode,
+    Language
+}
+
+fn setup_pausemenu(
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let ui_camera = match camera_query.single() {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    commands.spawn((
+        PauseMenuRootNode,
+        widgets::modal(&theme, ui_camera, PauseMenuPanel)
+    ));
+}
+
+fn cleanup_pausemenu(
+    query: Query<Entity, With<PauseMenuRootNode>>,
+    mut commands: Commands
+) {
+    let pausemenu_rootnode = match query.single() {
+        Ok(n) => n,
+        Err(_) => return,
+    };
+
+    commands
+        .entity(pausemenu_rootnode)
+        .despawn();
+}
+
+fn setup_main_page(
+    panel_query: Query<Entity, With<PauseMenuPanel>>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let panel = match panel_query.single() {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+
+    commands.entity(panel).with_child((
+        PauseMenuPageNode,
+        widgets::column(&theme),
+        children![
+            widgets::title_text(&theme, UiString::key("pause.title")),
+            (
+                PauseMenuButtonAction::Resume,
+                BackButton,
+                widgets::button(&theme, UiString::key("pause.resume"))
+            ),
+            (
+                PauseMenuButtonAction::Save,
+                widgets::button(&theme, UiString::key("pause.save"))
+            ),
+            (
+                PauseMenuButtonAction::Settings,
+                widgets::button(&theme, UiString::key("pause.settings"))
+            ),
+            (
+                PauseMenuButtonAction::Quit,
+                widgets::button(&theme, UiString::key("pause.quit"))
+            )
+        ]
+    ));
+}
+
+fn setup_settings_page(
+    panel_query: Query<Entity, With<PauseMenuPanel>>,
+    exposed_config: Res<ExposedConfig>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let panel = match panel_query.single() {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+
+    commands.entity(panel).with_child((
+        PauseMenuPageNode,
+        widgets::column(&theme),
+        children![
+            widgets::title_text(&theme, UiString::key("settings.title")),
+            (
+                PauseMenuButtonAction::ToggleAutosaveOnMapEnter,
+                generate_settings_button(&theme, SettingsLabel::AutosaveOnMapEnter, &exposed_config)
+            ),
+            (
+                PauseMenuButtonAction::ToggleAutosaveOnShopExit,
+                generate_settings_button(&theme, SettingsLabel::AutosaveOnShopExit, &exposed_config)
+            ),
+            (
+                PauseMenuButtonAction::CycleAutosaveEveryNSteps,
+                generate_settings_button(&theme, SettingsLabel::AutosaveEveryNSteps, &exposed_config)
+            ),
+            (
+                PauseMenuButtonAction::ToggleMovementMode,
+                generate_settings_button(&theme, SettingsLabel::MovementMode, &exposed_config)
+            ),
+            (
+                PauseMenuButtonAction::CycleLanguage,
+                generate_settings_button(&theme, SettingsLabel::Language, &exposed_config)
+            ),
+            (
+                PauseMenuButtonAction::Controls,
+                widgets::button(&theme, UiString::key("settings.controls"))
+            ),
+            (
+                PauseMenuButtonAction::Back,
+                BackButton,
+                widgets::button(&theme, UiString::key("settings.back"))
+            )
+        ]
+    ));
+}
+
+// Sorted by action, so the list doesn't change order between visits
+fn setup_controls_page(
+    panel_query: Query<Entity, With<PauseMenuPanel>>,
+    exposed_config: Res<ExposedConfig>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let panel = match panel_query.single() {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+
+    let mut actions: Vec<&String> = exposed_config.keyboard_bindings.exploration_controls.keys()
+        .chain(exposed_config.controller_bindings.exploration_controls.keys())
+        .collect();
+    actions.sort();
+    actions.dedup();
+
+    commands.entity(panel).with_children(|panel| {
+        panel.spawn((PauseMenuPageNode, widgets::column(&theme))).with_children(|page| {
+            page.spawn(widgets::title_text(&theme, UiString::key("controls.title")));
+            page.spawn(widgets::list(&theme)).with_children(|list| {
+                for action in actions {
+                    list.spawn(widgets::small_text(&theme, binding_text(action, &exposed_config)));
+                }
+            });
+            page.spawn((
+                PauseMenuButtonAction::BackToSettings,
+                BackButton,
+                widgets::button(&theme, UiString::key("settings.back"))
+            ));
+        });
+    });
+}
+
+fn setup_confirmquit_page(
+    panel_query: Query<Entity, With<PauseMenuPanel>>,
+    theme: Res<UiTheme>,
+    mut commands: Commands
+) {
+    let panel = match panel_query.single() {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+
+    commands.entity(panel).with_child((
+        PauseMenuPageNode,
+        widgets::column(&theme),
+        children![
+            widgets::title_text(&theme, UiString::key("quit.title")),
+            widgets::body_text(&theme, UiString::key("quit.warning")),
+            (
+                PauseMenuButtonAction::ConfirmQuit,
+                widgets::button(&theme, UiString::key("quit.confirm"))
+            ),
+            (
+                PauseMenuButtonAction::Back,
+                BackButton,
+                InitialFocus,
+                widgets::button(&theme, UiString::key("quit.cancel"))
+            )
+        ]
+    ));
+}
+
+fn cleanup_page(
+    query: Query<Entity, With<PauseMenuPageNode>>,
+    mut commands: Commands
+) {
+    for page in &query {
+        commands.entity(page).despawn();
+    }
+}
+
+
+/////////////////////////////////////////
+// BUTTON FUNCTIONALITY
+
+#[derive(Component)]
+enum PauseMenuButtonAction {
+    Resume,
+    Save,
+    Settings,
+    Quit,
+    ConfirmQuit,
+    ToggleAutosaveOnMapEnter,
+    ToggleAutosaveOnShopExit,
+    CycleAutosaveEveryNSteps,
+    ToggleMovementMode,
+    CycleLanguage,
+    Controls,
+    BackToSettings,
+    Back
+}
+
+fn pausemenu_action_system(
+    interaction_query: Query<
+        (&Interaction, &PauseMenuButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut exposed_config: ResMut<ExposedConfig>,
+    saves_dir: Res<SavesDir>,
+    mut save_requests: MessageWriter<SaveRequest>,
+    mut next_game_mode_state: ResMut<NextState<GameModeState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut next_pause_menu_page: ResMut<NextState<PauseMenuPage>>,
+) {
+    for (interaction, menu_button_action) in &interaction_query {
+        if interaction != &Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button_action {
+            PauseMenuButtonAction::Resume => {
+                next_pause_state.set(PauseState::Running);
+            },
+            PauseMenuButtonAction::Save => {
+                save_requests.write(SaveRequest { slot: next_manual_slot(&saves_dir) });
+            },
+            PauseMenuButtonAction::Settings => {
+                next_pause_menu_page.set(PauseMenuPage::Settings);
+            },
+            PauseMenuButtonAction::Quit => {
+                next_pause_menu_page.set(PauseMenuPage::ConfirmQuit);
+            },
+            PauseMenuButtonAction::ConfirmQuit => {
+                next_game_mode_state.set(GameModeState::MainMenu);
+            },
+            PauseMenuButtonAction::Back => {
+                next_pause_menu_page.set(PauseMenuPage::Main);
+            },
+            PauseMenuButtonAction::Controls => {
+                next_pause_menu_page.set(PauseMenuPage::Controls);
+            },
+            PauseMenuButtonAction::BackToSettings => {
+                next_pause_menu_page.set(PauseMenuPage::Settings);
+            },
+            PauseMenuButtonAction::ToggleAutosaveOnMapEnter => {
+                exposed_config.autosave.on_map_enter = !exposed_config.autosave.on_map_enter;
+                write_settings(&exposed_config);
+            },
+            PauseMenuButtonAction::ToggleAutosaveOnShopExit => {
+                exposed_config.autosave.on_shop_exit = !exposed_config.autosave.on_shop_exit;
+                write_settings(&exposed_config);
+            },
+            PauseMenuButtonAction::CycleAutosaveEveryNSteps => {
+                let current = AUTOSAVE_STEP_OPTIONS
+                    .iter()
+                    .position(|n| *n == exposed_config.autosave.every_n_steps)
+                    .unwrap_or(0);
+                exposed_config.autosave.every_n_steps =
+                    AUTOSAVE_STEP_OPTIONS[(current + 1) % AUTOSAVE_STEP_OPTIONS.len()];
+                write_settings(&exposed_config);
+            },
+            PauseMenuButtonAction::ToggleMovementMode => {
+                exposed_config.movement.mode = match exposed_config.movement.mode {
+                    MovementMode::Grid => MovementMode::Continuous,
+                    MovementMode::Continuous => MovementMode::Grid,
+                };
+                write_settings(&exposed_config);
+            },
+            PauseMenuButtonAction::CycleLanguage => {
+                let languages = available_languages();
+                if languages.is_empty() {
+                    continue;
+                }
+                let next = languages
+                    .iter()
+                    .position(|language| *language == exposed_config.language)
+                    .map_or(0, |current| (current + 1) % languages.len());
+                exposed_config.language = languages[next].clone();
+                write_settings(&exposed_config);
+            },
+        }
+    }
+}
+
+fn write_settings(exposed_config: &ExposedConfig) {
+    if let Err(e) = update_exposed_config_file(exposed_config) {
+        error!("{}", e);
+    }
+}
+
+fn update_settings_labels(
+    exposed_config: Res<ExposedConfig>,
+    label_query: Query<(&SettingsLabel, &Children)>,
+    mut text_query: Query<&mut UiString>,
+) {
+    if !exposed_config.is_changed() {
+        return;
+    }
+
+    for (settings_label, children) in &label_query {
+        for child in children.iter() {
+            if let Ok(mut ui_string) = text_query.get_mut(child) {
+                ui_string.set_if_neq(settings_label_text(settings_label, &exposed_config));
+            }
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+// The language label is the language's own name from its table, so it reads correctly once the
+// table has switched
+fn settings_label_text(settings_label: &SettingsLabel, exposed_config: &ExposedConfig) -> UiString {
+    let on_off = |b: bool| UiString::key(if b { "settings.on" } else { "settings.off" });
+    match settings_label {
+        SettingsLabel::AutosaveOnMapEnter => {
+            UiString::key_with("settings.autosave_map_enter", [on_off(exposed_config.autosave.on_map_enter)])
+        },
+        SettingsLabel::AutosaveOnShopExit => {
+            UiString::key_with("settings.autosave_shop_exit", [on_off(exposed_config.autosave.on_shop_exit)])
+        },
+        SettingsLabel::AutosaveEveryNSteps => match exposed_config.autosave.every_n_steps {
+            0 => UiString::key_with("settings.autosave_every_n_steps", [on_off(false)]),
+            n => UiString::key_with("settings.autosave_every_n_steps", [n]),
+        },
+        SettingsLabel::MovementMode => match exposed_config.movement.mode {
+            MovementMode::Grid => UiString::key_with("settings.movement_mode", [UiString::key("settings.movement_grid")]),
+            MovementMode::Continuous => UiString::key_with("settings.movement_mode", [UiString::key("settings.movement_continuous")]),
+        },
+        SettingsLabel::Language => UiString::key_with("settings.language", [UiString::key("language.name")]),
+    }
+}
+
+// "Walk Forward: KeyW / DPadUp", with "-" for an action without a binding on one of the two
+fn binding_text(action: &str, exposed_config: &ExposedConfig) -> UiString {
+    let key = exposed_config.keyboard_bindings.exploration_controls
+        .get(action)
+        .map_or(String::from("-"), |key| format!("{:?}", key));
+    let button = exposed_config.controller_bindings.exploration_controls
+        .get(action)
+        .map_or(String::from("-"), |button| format!("{:?}", button));
+    UiString::key_with("controls.binding", [UiString::action(action), key.into(), button.into()])
+}
+
+fn generate_settings_button(theme: &UiTheme, settings_label: SettingsLabel, exposed_config: &ExposedConfig) -> impl Bundle {
+    let text = settings_label_text(&settings_label, exposed_config);
+    (
+        settings_label,
+        widgets::button(theme, text)
+    )
+}
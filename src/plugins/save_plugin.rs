@@ -0,0 +1,379 @@
+// This plugin reads and writes save files, and handles the triggers that create them during play:
+// - autosaves (entering a map, leaving a shop, every N completed steps), rotating through a few
+//   slots so the oldest autosave is the one overwritten
+// - quicksave and quickload, bound in ExposedConfig and usable from Explore
+// - manual saves (from the pause menu), which fill MANUAL_SAVE_SLOTS before overwriting the oldest
+//
+// Save files are RON written through Reflect, the same as ExposedConfig (see src/reflect_ron.rs).
+// Each SaveSlot maps to one file in the SavesDir, plus a PNG thumbnail of the same name.
+
+use std::{
+    fs,
+    collections::HashMap,
+    path::Path,
+    time::{ SystemTime, UNIX_EPOCH },
+};
+use bevy::{
+    prelude::*,
+    core_pipeline::Skybox,
+};
+
+pub mod thumbnail;
+
+use crate::{
+    reflect_ron::{ from_ron_str, to_ron_string },
+    plugins::{
+        camera_plugin::NavigateCamera,
+        save_plugin::thumbnail::{ despawn_pending_thumbnail_captures, spawn_thumbnail_capture },
+        exposed_config_plugin::{ ActionInput, ExposedConfig },
+        explore_plugin::{
+            map::{ CurrentMap, MapEntered, load_map_file },
+            movement::{ CardinalDirection, ExplorationMovementData, MovementStepCompleted },
+        },
+        manage_state_plugin::{
+            GameModeState, InGameSubstate, PauseState, DialogueState,
+            ingame_state_plugin::{ InGameData, party::PartyMember },
+        },
+    },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+pub const SAVES_DIRPATH: &str = "saves";
+const MANUAL_SAVE_SLOTS: u8 = 10;
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<SaveRequest>();
+        app.init_resource::<SavesDir>();
+        app.init_resource::<PendingLoad>();
+        app.init_resource::<StepsSinceAutosave>();
+
+        app.add_systems(
+            Update,
+            (
+                quicksave_controls
+                    .run_if(in_state(PauseState::Running))
+                    .run_if(in_state(DialogueState::Closed)),
+                autosave_triggers,
+                apply_pending_load
+            ).run_if(in_state(InGameSubstate::Explore))
+        );
+        app.add_systems(
+            Update,
+            write_requested_saves
+                .after(autosave_triggers)
+                .after(quicksave_controls)
+                .run_if(in_state(GameModeState::InGame))
+        );
+        app.add_systems(OnExit(InGameSubstate::Shop), request_shop_exit_autosave);
+        app.add_systems(OnExit(GameModeState::InGame), (reset_autosave_steps, despawn_pending_thumbnail_captures));
+    }
+}
+
+
+/////////////////////////////////////////
+// SAVE DATA
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveSlot {
+    Manual(u8),
+    Autosave(u8),
+    Quicksave
+}
+
+impl SaveSlot {
+    fn file_stem(&self, saves_dir: &SavesDir) -> String {
+        match self {
+            SaveSlot::Manual(n) => format!("{}/slot_{}", saves_dir.0, n),
+            SaveSlot::Autosave(n) => format!("{}/autosave_{}", saves_dir.0, n),
+            SaveSlot::Quicksave => format!("{}/quicksave", saves_dir.0),
+        }
+    }
+
+    fn filepath(&self, saves_dir: &SavesDir) -> String {
+        format!("{}.ron", self.file_stem(saves_dir))
+    }
+
+    pub fn thumbnail_filepath(&self, saves_dir: &SavesDir) -> String {
+        format!("{}.png", self.file_stem(saves_dir))
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            SaveSlot::Manual(n) => format!("Slot {}", n + 1),
+            SaveSlot::Autosave(n) => format!("Autosave {}", n + 1),
+            SaveSlot::Quicksave => String::from("Quicksave"),
+        }
+    }
+}
+
+// saved_at is milliseconds since the unix epoch, used to sort saves and to pick which autosave
+// slot to overwrite - in milliseconds so saves written in the same second (an autosave on entering
+// a map, then a quicksave) still sort in the order they were written. flags and party default to
+// empty so saves written before they existed still load; such a save keeps the party the game was
+// started with.
+#[derive(Reflect, Debug, Clone)]
+pub struct SaveData {
+    pub slot: SaveSlot,
+    pub saved_at: u64,
+    pub character_name: String,
+    pub map_filepath: String,
+    pub map_name: String,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub cardinal_facing: Option<CardinalDirection>,
+    #[reflect(default)]
+    pub flags: HashMap<String, String>,
+    #[reflect(default)]
+    pub party: Vec<PartyMember>
+}
+
+/// The directory saves are read from and written to: SAVES_DIRPATH, unless another is inserted
+/// before SavePlugin is added - tests play real games, which autosave, so each test game gets a
+/// directory of its own and never touches the player's saves (or another test's)
+#[derive(Resource, Debug, Clone)]
+pub struct SavesDir(pub String);
+
+impl Default for SavesDir {
+    fn default() -> Self {
+        SavesDir(String::from(SAVES_DIRPATH))
+    }
+}
+
+/// Ask for the current game to be written to a slot. Read by write_requested_saves.
+#[derive(Message, Debug)]
+pub struct SaveRequest {
+    pub slot: SaveSlot
+}
+
+/// A save waiting to be applied to the world. Set by the load menu (before entering InGame) or by
+/// quickload; consumed by apply_pending_load once in Explore.
+#[derive(Resource, Default)]
+pub struct PendingLoad(pub Option<SaveData>);
+
+#[derive(Resource, Default)]
+struct StepsSinceAutosave(u32);
+
+
+/////////////////////////////////////////
+// FILE FUNCTIONS
+
+pub fn write_save(saves_dir: &SavesDir, save: &SaveData) -> Result<(), String> {
+    fs::create_dir_all(&saves_dir.0).map_err(|e| e.to_string())?;
+    let save_ron_str = to_ron_string(save)?;
+    fs::write(save.slot.filepath(saves_dir), save_ron_str).map_err(|e| e.to_string())
+}
+
+pub fn read_save(saves_dir: &SavesDir, slot: &SaveSlot) -> Result<SaveData, String> {
+    let save_ron_str = fs::read_to_string(slot.filepath(saves_dir)).map_err(|e| e.to_string())?;
+    from_ron_str(&save_ron_str)
+}
+
+// A missing thumbnail is not an error - the save may have been written without a window to
+// render in
+pub fn delete_save(saves_dir: &SavesDir, slot: &SaveSlot) -> Result<(), String> {
+    fs::remove_file(slot.filepath(saves_dir)).map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(slot.thumbnail_filepath(saves_dir));
+    Ok(())
+}
+
+/// Every readable save in saves_dir, most recently written first
+pub fn list_saves(saves_dir: &SavesDir) -> Vec<SaveData> {
+    let entries = match fs::read_dir(&saves_dir.0) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut saves: Vec<SaveData> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .filter_map(|path| match read_save_file(&path) {
+            Ok(save) => Some(save),
+            Err(e) => {
+                warn!("skipping unreadable save {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect();
+
+    saves.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    saves
+}
+
+fn read_save_file(path: &Path) -> Result<SaveData, String> {
+    let save_ron_str = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    from_ron_str(&save_ron_str)
+}
+
+/// Of the given slots, the first with nothing in it, otherwise the one saved to longest ago
+fn empty_or_oldest_slot(saves_dir: &SavesDir, slots: impl Iterator<Item = SaveSlot>) -> Option<SaveSlot> {
+    let saves = list_saves(saves_dir);
+    slots.min_by_key(|slot| {
+        saves.iter()
+            .find(|save| save.slot == *slot)
+            .map(|save| save.saved_at)
+            .unwrap_or(0)
+    })
+}
+
+/// Autosaves rotate through rotating_slots slots
+fn next_autosave_slot(saves_dir: &SavesDir, rotating_slots: u8) -> SaveSlot {
+    empty_or_oldest_slot(saves_dir, (0..rotating_slots.max(1)).map(SaveSlot::Autosave))
+        .unwrap_or(SaveSlot::Autosave(0))
+}
+
+pub fn next_manual_slot(saves_dir: &SavesDir) -> SaveSlot {
+    empty_or_oldest_slot(saves_dir, (0..MANUAL_SAVE_SLOTS).map(SaveSlot::Manual))
+        .unwrap_or(SaveSlot::Manual(0))
+}
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+fn quicksave_controls(
+    saves_dir: Res<SavesDir>,
+    mut action_input: ResMut<ActionInput>,
+    mut save_requests: MessageWriter<SaveRequest>,
+    mut pending_load: ResMut<PendingLoad>,
+) {
+    if action_input.consume("Quicksave") {
+        save_requests.write(SaveRequest { slot: SaveSlot::Quicksave });
+    }
+    else if action_input.consume("Quickload") {
+        match read_save(&saves_dir, &SaveSlot::Quicksave) {
+            Ok(save) => { pending_load.0 = Some(save); },
+            Err(e) => { warn!("no quicksave to load: {}", e); }
+        }
+    }
+}
+
+fn autosave_triggers(
+    saves_dir: Res<SavesDir>,
+    exposed_config: Res<ExposedConfig>,
+    mut map_entered: MessageReader<MapEntered>,
+    mut steps_completed: MessageReader<MovementStepCompleted>,
+    mut steps_since_autosave: ResMut<StepsSinceAutosave>,
+    mut save_requests: MessageWriter<SaveRequest>,
+) {
+    let settings = &exposed_config.autosave;
+    let entered_map = map_entered.read().count() > 0;
+    steps_since_autosave.0 += steps_completed.read().count() as u32;
+
+    let step_autosave_due = settings.every_n_steps > 0 && steps_since_autosave.0 >= settings.every_n_steps;
+
+    if (entered_map && settings.on_map_enter) || step_autosave_due {
+        steps_since_autosave.0 = 0;
+        save_requests.write(SaveRequest { slot: next_autosave_slot(&saves_dir, settings.rotating_slots) });
+    }
+}
+
+fn request_shop_exit_autosave(
+    saves_dir: Res<SavesDir>,
+    exposed_config: Res<ExposedConfig>,
+    mut save_requests: MessageWriter<SaveRequest>,
+) {
+    if exposed_config.autosave.on_shop_exit {
+        save_requests.write(SaveRequest { slot: next_autosave_slot(&saves_dir, exposed_config.autosave.rotating_slots) });
+    }
+}
+
+fn reset_autosave_steps(mut steps_since_autosave: ResMut<StepsSinceAutosave>) {
+    steps_since_autosave.0 = 0;
+}
+
+fn write_requested_saves(
+    mut commands: Commands,
+    saves_dir: Res<SavesDir>,
+    mut images: ResMut<Assets<Image>>,
+    mut save_requests: MessageReader<SaveRequest>,
+    camera_q: Single<(&Transform, Option<&Skybox>), With<NavigateCamera>>,
+    movement_data: Res<ExplorationMovementData>,
+    ingame_data: Res<InGameData>,
+    current_map: Res<CurrentMap>,
+) {
+    let (camera_transform, skybox) = camera_q.into_inner();
+
+    for request in save_requests.read() {
+        let save = SaveData {
+            slot: request.slot,
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            character_name: ingame_data.name.clone(),
+            map_filepath: current_map.filepath.clone(),
+            map_name: current_map.definition.name.clone(),
+            translation: camera_transform.translation.to_array(),
+            rotation: camera_transform.rotation.to_array(),
+            cardinal_facing: movement_data.cardinal_facing,
+            flags: ingame_data.flags.clone(),
+            party: ingame_data.party.clone()
+        };
+
+        match write_save(&saves_dir, &save) {
+            Ok(_) => {
+                info!("Saved to {}", save.slot.label());
+                spawn_thumbnail_capture(
+                    &mut commands,
+                    &mut images,
+                    camera_transform,
+                    skybox,
+                    save.slot.thumbnail_filepath(&saves_dir)
+                );
+            },
+            Err(e) => error!("failure writing {}: {}", save.slot.label(), e)
+        }
+    }
+}
+
+/// Put the player where the pending save says, dropping any movement that was queued
+fn apply_pending_load(
+    mut commands: Commands,
+    mut pending_load: ResMut<PendingLoad>,
+    mut ingame_data: ResMut<InGameData>,
+    mut movement_data: ResMut<ExplorationMovementData>,
+    current_map: Res<CurrentMap>,
+    camera_transform_q: Single<&mut Transform, With<NavigateCamera>>,
+) {
+    let save = match pending_load.0.take() {
+        Some(s) => s,
+        None => return,
+    };
+
+    if save.map_filepath != current_map.filepath {
+        match load_map_file(&save.map_filepath) {
+            Ok(map) => commands.insert_resource(map),
+            Err(e) => error!("failure loading map from save: {}", e)
+        }
+    }
+
+    ingame_data.name = save.character_name.clone();
+    ingame_data.flags = save.flags.clone();
+    if !save.party.is_empty() {
+        ingame_data.party = save.party.clone();
+    }
+
+    let mut camera_transform = camera_transform_q.into_inner();
+    camera_transform.translation = Vec3::from_array(save.translation);
+    camera_transform.rotation = Quat::from_array(save.rotation);
+
+    movement_data.current_movement_timer = None;
+    movement_data.input_buffer.clear();
+    movement_data.held_movement = None;
+    movement_data.current_movement_command = None;
+    movement_data.command_queue.clear();
+    movement_data.cardinal_facing = save.cardinal_facing;
+    movement_data.oriented_to_cardinal_directions = save.cardinal_facing.is_some();
+
+    info!("Loaded {}", save.slot.label());
+}
@@ -0,0 +1,110 @@
+/// Thumbnails are small PNGs written next to each save file, showing what NavigateCamera saw when
+/// the save was made.
+///
+/// Rather than screenshotting the window (which would include any UI drawn on top), a temporary
+/// ThumbnailCamera is placed where NavigateCamera is and rendered into a small image. That image is
+/// read back with Bevy's Screenshot component and written to disk, after which the camera is
+/// despawned. The previous thumbnail is removed as the capture starts, so a slot whose capture
+/// fails, or never runs (without a renderer, or when the game is left first), shows no thumbnail
+/// rather than an older save's. Captures still pending when the game is left are despawned by
+/// despawn_pending_thumbnail_captures.
+use std::fs;
+use bevy::{
+    prelude::*,
+    asset::RenderAssetUsages,
+    camera::RenderTarget,
+    core_pipeline::Skybox,
+    image::{ BevyDefault, CompressedImageFormats, ImageSampler, ImageType },
+    render::{
+        render_resource::TextureFormat,
+        view::screenshot::{ Screenshot, ScreenshotCaptured },
+    },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const THUMBNAIL_SIZE: u32 = 128;
+
+#[derive(Component)]
+#[require(Camera3d)]
+pub struct ThumbnailCamera {
+    thumbnail_filepath: String
+}
+
+// The Screenshot reading back a ThumbnailCamera's image
+#[derive(Component)]
+pub struct ThumbnailScreenshot;
+
+
+pub fn spawn_thumbnail_capture(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    camera_transform: &Transform,
+    skybox: Option<&Skybox>,
+    thumbnail_filepath: String,
+) {
+    let _ = fs::remove_file(&thumbnail_filepath);
+    let target = images.add(Image::new_target_texture(
+        THUMBNAIL_SIZE,
+        THUMBNAIL_SIZE,
+        TextureFormat::bevy_default()
+    ));
+
+    let mut thumbnail_camera = commands.spawn((
+        ThumbnailCamera { thumbnail_filepath: thumbnail_filepath.clone() },
+        Camera {
+            target: RenderTarget::Image(target.clone().into()),
+            ..default()
+        },
+        *camera_transform
+    ));
+    if let Some(s) = skybox {
+        thumbnail_camera.insert(s.clone());
+    }
+    let thumbnail_camera = thumbnail_camera.id();
+
+    commands
+        .spawn((ThumbnailScreenshot, Screenshot::image(target)))
+        .observe(move |captured: On<ScreenshotCaptured>, mut commands: Commands| {
+            match captured.image.clone().try_into_dynamic() {
+                Ok(dyn_img) => {
+                    if let Err(e) = dyn_img.to_rgb8().save(&thumbnail_filepath) {
+                        error!("failure writing thumbnail {}: {}", thumbnail_filepath, e);
+                    }
+                },
+                Err(e) => error!("failure converting thumbnail {}: {}", thumbnail_filepath, e)
+            }
+            commands.entity(thumbnail_camera).despawn();
+        });
+}
+
+/// Despawn any thumbnail capture that hasn't been rendered yet - without a renderer, or when the
+/// game is left in the same frame as a save - so its camera doesn't stay around
+pub fn despawn_pending_thumbnail_captures(
+    mut commands: Commands,
+    thumbnail_camera_q: Query<(Entity, &ThumbnailCamera)>,
+    thumbnail_screenshot_q: Query<Entity, With<ThumbnailScreenshot>>,
+) {
+    for (entity, thumbnail_camera) in thumbnail_camera_q.iter() {
+        warn!("thumbnail {} was never captured", thumbnail_camera.thumbnail_filepath);
+        commands.entity(entity).despawn();
+    }
+    for entity in thumbnail_screenshot_q.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Read a thumbnail PNG from disk into an Image, for use in an ImageNode. Saves are kept outside of
+/// the assets folder, so these don't go through the AssetServer.
+pub fn read_thumbnail(thumbnail_filepath: &str) -> Option<Image> {
+    let bytes = fs::read(thumbnail_filepath).ok()?;
+    Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::RENDER_WORLD
+    ).ok()
+}
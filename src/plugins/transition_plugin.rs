@@ -0,0 +1,339 @@
+// This plugin plays a transition (fade, wipe or swirl) whenever GameModeState or InGameSubstate
+// changes, without the screens having to know about it - they keep calling next_state.set.
+//
+// A requested state change is taken out of NextState and held while the cover comes in over the
+// old screen. Once the screen is covered the change is let through, so OnExit/OnEnter swap the
+// screens out of sight, and the cover then goes away over the new screen. While a transition plays,
+// ActionInput is dropped and the cover blocks the mouse.
+//
+// Which effect plays is read from TRANSITIONS_FILEPATH, keyed by the name of the state being
+// entered.
+
+use std::{ collections::HashMap, f32::consts::TAU, fmt::Debug, fs, mem };
+use bevy::{ prelude::*, state::state::FreelyMutableState, ui::FocusPolicy };
+
+use crate::{
+    plugins::{
+        exposed_config_plugin::{ ActionInput, ActionInputSystems, collect_action_input },
+        manage_state_plugin::{ GameModeState, InGameSubstate },
+    },
+    reflect_ron::from_ron_str,
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const TRANSITIONS_FILEPATH: &str = "config/transitions.ron";
+
+// How many times the swirl turns on its way in (and again on its way out)
+const SWIRL_TURNS: f32 = 1.5;
+
+#[derive(Reflect, Resource, Debug, Clone)]
+pub struct TransitionConfig {
+    pub color: Color,
+    pub default: TransitionStyle,
+    /// Keyed by the entered state's name, e.g. "InGame" or "Shop"
+    pub by_state: HashMap<String, TransitionStyle>
+}
+
+#[derive(Reflect, Debug, Clone, Copy)]
+pub struct TransitionStyle {
+    pub effect: TransitionEffect,
+    /// Seconds for each half - covering the old screen, then uncovering the new one
+    pub duration: f32
+}
+
+#[derive(Reflect, Debug, Clone, Copy, PartialEq)]
+pub enum TransitionEffect {
+    /// Hard cut, as if there were no transition
+    None,
+    Fade,
+    /// The cover sweeps in from the left, and out to the right
+    Wipe,
+    /// A spinning square grows from the centre until it covers the screen (the classic encounter
+    /// swirl)
+    Swirl
+}
+
+impl Default for TransitionConfig {
+    fn default() -> Self {
+        TransitionConfig {
+            color: Color::BLACK,
+            default: TransitionStyle { effect: TransitionEffect::Fade, duration: 0.3 },
+            by_state: HashMap::new()
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_transition_config());
+        app.init_resource::<ScreenTransition>();
+        app.add_systems(
+            PreUpdate,
+            block_input_during_transition.in_set(ActionInputSystems).after(collect_action_input)
+        );
+        // Last, so every next_state.set made this frame has been made
+        app.add_systems(
+            Last,
+            (
+                (hold_state_change::<GameModeState>, hold_state_change::<InGameSubstate>),
+                advance_transition,
+                draw_transition_cover
+            ).chain()
+        );
+    }
+}
+
+
+/////////////////////////////////////////
+// TRANSITION STATE
+
+#[derive(Default, PartialEq)]
+enum TransitionPhase {
+    #[default]
+    Idle,
+    /// The cover is coming in over the old screen
+    Covering,
+    /// The held state change has been let through; waiting a frame for the new screen to spawn
+    Covered,
+    /// The cover is going away over the new screen
+    Uncovering
+}
+
+// Lets the held state change through, setting the NextState it was taken from
+type HeldStateChange = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+#[derive(Resource, Default)]
+pub struct ScreenTransition {
+    phase: TransitionPhase,
+    style: Option<TransitionStyle>,
+    /// 0 when the screen is clear, 1 when it is fully covered
+    coverage: f32,
+    held: Option<HeldStateChange>
+}
+
+impl ScreenTransition {
+    pub fn is_playing(&self) -> bool {
+        self.phase != TransitionPhase::Idle
+    }
+
+    // A change requested mid-transition replaces the one being held, and the cover heads back in
+    // from wherever it is
+    fn hold(&mut self, style: TransitionStyle, held: HeldStateChange) {
+        self.phase = TransitionPhase::Covering;
+        self.style = Some(style);
+        self.held = Some(held);
+    }
+}
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+fn hold_state_change<S: FreelyMutableState + Debug>(
+    next_state: Option<ResMut<NextState<S>>>,
+    config: Res<TransitionConfig>,
+    mut transition: ResMut<ScreenTransition>
+) {
+    // sub states only have a NextState while their source state is active
+    let mut next_state = match next_state {
+        Some(n) => n,
+        None => return,
+    };
+    let state = match mem::take(next_state.bypass_change_detection()) {
+        NextState::Pending(s) => s,
+        NextState::Unchanged => return,
+    };
+
+    let style = config.by_state
+        .get(&format!("{:?}", state))
+        .copied()
+        .unwrap_or(config.default);
+    transition.hold(style, Box::new(move |world: &mut World| {
+        if let Some(mut next_state) = world.get_resource_mut::<NextState<S>>() {
+            next_state.set(state);
+        }
+    }));
+}
+
+// Real time, as virtual time may be paused (e.g. quitting from the pause menu)
+fn advance_transition(
+    time: Res<Time<Real>>,
+    mut transition: ResMut<ScreenTransition>,
+    mut commands: Commands
+) {
+    let style = match transition.style {
+        Some(s) => s,
+        None => return,
+    };
+    let step = match style.effect {
+        TransitionEffect::None => 1.,
+        _ => time.delta_secs() / style.duration.max(f32::EPSILON),
+    };
+
+    match transition.phase {
+        TransitionPhase::Idle => {}
+        TransitionPhase::Covering => {
+            transition.coverage = (transition.coverage + step).min(1.);
+            if transition.coverage >= 1. {
+                if let Some(held) = transition.held.take() {
+                    commands.queue(held);
+                }
+                transition.phase = TransitionPhase::Covered;
+            }
+        }
+        TransitionPhase::Covered => {
+            transition.phase = TransitionPhase::Uncovering;
+        }
+        TransitionPhase::Uncovering => {
+            transition.coverage = (transition.coverage - step).max(0.);
+            if transition.coverage <= 0. {
+                transition.phase = TransitionPhase::Idle;
+                transition.style = None;
+            }
+        }
+    }
+}
+
+fn block_input_during_transition(
+    transition: Res<ScreenTransition>,
+    mut action_input: ResMut<ActionInput>
+) {
+    if transition.is_playing() {
+        action_input.clear();
+    }
+}
+
+
+/////////////////////////////////////////
+// NODE STRUCTURE
+
+// The root covers the whole screen and blocks the mouse for as long as the transition plays; the
+// cover inside it is what's drawn
+#[derive(Component)]
+struct TransitionRootNode;
+
+#[derive(Component)]
+struct TransitionCover;
+
+fn draw_transition_cover(
+    transition: Res<ScreenTransition>,
+    config: Res<TransitionConfig>,
+    camera_query: Query<Entity, With<IsDefaultUiCamera>>,
+    root_query: Query<Entity, With<TransitionRootNode>>,
+    mut cover_query: Query<(&mut Node, &mut BackgroundColor, &mut UiTransform), With<TransitionCover>>,
+    mut commands: Commands
+) {
+    let style = match (transition.is_playing(), transition.style) {
+        (true, Some(s)) => s,
+        _ => {
+            for root in &root_query {
+                commands.entity(root).despawn();
+            }
+            return;
+        }
+    };
+
+    let (mut node, mut background_color, mut ui_transform) = match cover_query.single_mut() {
+        Ok(c) => c,
+        Err(_) => {
+            let ui_camera = match camera_query.single() {
+                Ok(c) => c,
+                Err(_) => return,
+            };
+            commands.spawn((
+                TransitionRootNode,
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+                FocusPolicy::Block,
+                GlobalZIndex(i32::MAX),
+                UiTargetCamera(ui_camera),
+                children![(
+                    TransitionCover,
+                    Node::default(),
+                    BackgroundColor(Color::NONE),
+                    UiTransform::default()
+                )]
+            ));
+            return;
+        }
+    };
+
+    let coverage = transition.coverage;
+    let (new_node, color, rotation) = match style.effect {
+        TransitionEffect::None | TransitionEffect::Fade => (
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            config.color.with_alpha(config.color.alpha() * coverage),
+            0.
+        ),
+        // pinned to the left while covering, to the right while uncovering
+        TransitionEffect::Wipe => {
+            let pinned = match transition.phase {
+                TransitionPhase::Uncovering => UiRect { right: Val::Px(0.), ..default() },
+                _ => UiRect { left: Val::Px(0.), ..default() },
+            };
+            (
+                Node {
+                    width: Val::Percent(100.0 * coverage),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    left: pinned.left,
+                    right: pinned.right,
+                    ..default()
+                },
+                config.color,
+                0.
+            )
+        }
+        // a square 1.5x the longer side covers the screen at any angle
+        TransitionEffect::Swirl => (
+            Node {
+                width: Val::VMax(150.0 * coverage),
+                height: Val::VMax(150.0 * coverage),
+                flex_shrink: 0.,
+                ..default()
+            },
+            config.color,
+            coverage * SWIRL_TURNS * TAU
+        ),
+    };
+
+    node.set_if_neq(new_node);
+    background_color.set_if_neq(BackgroundColor(color));
+    ui_transform.set_if_neq(UiTransform::from_rotation(Rot2::radians(rotation)));
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn load_transition_config() -> TransitionConfig {
+    let read = fs::read_to_string(TRANSITIONS_FILEPATH)
+        .map_err(|e| e.to_string())
+        .and_then(|ron_str| from_ron_str(&ron_str));
+    match read {
+        Ok(c) => c,
+        Err(e) => {
+            error!("failure loading {}, using default transitions: {}", TRANSITIONS_FILEPATH, e);
+            TransitionConfig::default()
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// This plugin holds what every menu screen shares: the UiTheme (read from config/ui_theme.ron, see
+// ui_plugin/theme.rs), the widget builders that use it (see ui_plugin/widgets.rs) and keyboard and
+// gamepad navigation between their buttons (see ui_plugin/focus.rs), and the string tables their
+// text is looked up in (see ui_plugin/localization.rs).
+//
+// Screens build their nodes from the widgets, rather than defining their own colours, button
+// generators and button styling systems.
+
+use bevy::{ prelude::*, ui::UiSystems };
+
+pub mod focus;
+pub mod localization;
+pub mod theme;
+pub mod widgets;
+
+use crate::plugins::{
+    exposed_config_plugin::ActionInputSystems,
+    ui_plugin::{
+        focus::{ FocusScopes, UiFocus, navigate_focus, track_focus_scopes, update_focus_target },
+        localization::{ FALLBACK_LANGUAGE, Localization, resolve_ui_strings, sync_language },
+        theme::load_ui_theme,
+        widgets::style_themed_buttons,
+    },
+};
+
+
+/////////////////////////////////////////
+// PLUGIN DEFINITION
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        // Loaded here rather than in a startup system, as the initial state's OnEnter systems
+        // (which may build screens) run before PreStartup
+        let theme = load_ui_theme(app.world().get_resource::<AssetServer>());
+        app.insert_resource(theme);
+        // the fallback language until ExposedConfig is loaded, so screens built before then still
+        // have text
+        app.insert_resource(Localization::load(FALLBACK_LANGUAGE));
+        app.init_resource::<FocusScopes>();
+        app.init_resource::<UiFocus>();
+        app.add_systems(
+            PreUpdate,
+            (
+                track_focus_scopes,
+                update_focus_target,
+                navigate_focus
+            ).chain().after(UiSystems::Focus).after(ActionInputSystems)
+        );
+        app.add_systems(Update, style_themed_buttons);
+        app.add_systems(
+            PostUpdate,
+            (sync_language, resolve_ui_strings).chain().before(UiSystems::Prepare)
+        );
+    }
+}
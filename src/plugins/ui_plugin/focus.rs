@@ -0,0 +1,213 @@
+/// Keyboard and gamepad navigation for menus.
+///
+/// Every screen_root, overlay_root and modal is a FocusScope; the most recently spawned scope that
+/// still exists is the active one, so an overlay takes focus from the screen beneath it and gives it
+/// back when it is despawned. Within the active scope, one ThemedButton at a time holds UiFocus:
+/// - "Menu Up/Down/Left/Right" move focus to the nearest button in that direction
+/// - "Confirm" presses the focused button
+/// - "Back" presses the scope's BackButton, if it has one
+///
+/// Pressing is done by setting the button's Interaction to Pressed, so screens handle keyboard,
+/// gamepad and mouse through the same action systems. The focus highlight is only shown once one of
+/// these actions is used, and is hidden again when the mouse moves.
+use bevy::{ prelude::*, window::CursorMoved };
+
+use crate::plugins::{
+    exposed_config_plugin::ActionInput,
+    ui_plugin::widgets::ThemedButton,
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+
+// How much sideways distance counts against a candidate, relative to distance in the direction
+// moved. Higher values prefer buttons in a straight line over nearer diagonal ones.
+const PERPENDICULAR_WEIGHT: f32 = 2.0;
+
+
+/////////////////////////////////////////
+// FOCUS STATE
+
+/// Marks a root node whose buttons can be navigated. Added by the container widgets.
+#[derive(Component, Default)]
+pub struct FocusScope;
+
+/// Given focus when its scope becomes active, instead of the scope's first button
+#[derive(Component)]
+pub struct InitialFocus;
+
+/// Pressed by the "Back" action while its scope is active (e.g. Return, Close, Cancel)
+#[derive(Component)]
+pub struct BackButton;
+
+/// Left out of navigation, for buttons with controls of their own (e.g. dialogue choices)
+#[derive(Component)]
+pub struct SkipFocus;
+
+#[derive(Resource, Default)]
+pub struct UiFocus {
+    pub entity: Option<Entity>,
+    /// Whether the highlight is drawn - only after menu actions are used, not with the mouse
+    pub visible: bool
+}
+
+type FocusableQuery<'w, 's> = Query<'w, 's, Has<InitialFocus>, (With<ThemedButton>, Without<SkipFocus>)>;
+
+// Scopes in the order they were spawned; the last one is active
+#[derive(Resource, Default)]
+pub(super) struct FocusScopes(Vec<Entity>);
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+pub(super) fn track_focus_scopes(
+    added_query: Query<Entity, Added<FocusScope>>,
+    scope_query: Query<(), With<FocusScope>>,
+    mut scopes: ResMut<FocusScopes>
+) {
+    scopes.0.retain(|scope| scope_query.get(*scope).is_ok());
+    scopes.0.extend(added_query.iter());
+}
+
+/// Keeps focus on a button of the active scope, and follows the mouse onto hovered buttons
+pub(super) fn update_focus_target(
+    scopes: Res<FocusScopes>,
+    children_query: Query<&Children>,
+    button_query: FocusableQuery,
+    hovered_query: Query<(Entity, &Interaction), (Changed<Interaction>, With<ThemedButton>, Without<SkipFocus>)>,
+    mut focus: ResMut<UiFocus>
+) {
+    let focusables = focusable_buttons(&scopes, &children_query, &button_query);
+
+    for (button, interaction) in &hovered_query {
+        if *interaction == Interaction::Hovered && focusables.contains(&button) {
+            focus.entity = Some(button);
+        }
+    }
+
+    if focus.entity.is_some_and(|e| focusables.contains(&e)) {
+        return;
+    }
+    let initial = focusables.iter()
+        .find(|button| button_query.get(**button).is_ok_and(|initial| initial))
+        .or(focusables.first())
+        .copied();
+    if focus.entity != initial {
+        focus.entity = initial;
+    }
+}
+
+/// Runs after UiSystems::Focus, so a press made here is seen by the Update systems this frame
+/// just as a mouse click would be
+pub(super) fn navigate_focus(
+    mut action_input: ResMut<ActionInput>,
+    mut cursor_moved: MessageReader<CursorMoved>,
+    scopes: Res<FocusScopes>,
+    children_query: Query<&Children>,
+    button_query: FocusableQuery,
+    back_query: Query<(), With<BackButton>>,
+    transform_query: Query<&UiGlobalTransform>,
+    mut interaction_query: Query<&mut Interaction>,
+    mut focus: ResMut<UiFocus>,
+    mut pressed_last_frame: Local<Option<Entity>>
+) {
+    // release the button pressed last frame, as a mouse button would be
+    if let Some(button) = pressed_last_frame.take() {
+        if let Ok(mut interaction) = interaction_query.get_mut(button) {
+            interaction.set_if_neq(Interaction::None);
+        }
+    }
+
+    if cursor_moved.read().count() > 0 {
+        focus.visible = false;
+    }
+
+    let focusables = focusable_buttons(&scopes, &children_query, &button_query);
+    if focusables.is_empty() {
+        return;
+    }
+
+    let directions = [
+        ("Menu Up", Vec2::NEG_Y),
+        ("Menu Down", Vec2::Y),
+        ("Menu Left", Vec2::NEG_X),
+        ("Menu Right", Vec2::X),
+    ];
+    for (action, direction) in directions {
+        if !action_input.consume(action) {
+            continue;
+        }
+        // the first press only reveals where focus is
+        if focus.visible {
+            let next = focus.entity
+                .and_then(|current| nearest_in_direction(current, direction, &focusables, &transform_query));
+            if next.is_some() {
+                focus.entity = next;
+            }
+        }
+        focus.visible = true;
+    }
+
+    let target = if action_input.consume("Confirm") {
+        focus.visible = true;
+        focus.entity
+    } else if action_input.consume("Back") {
+        focusables.iter().find(|button| back_query.get(**button).is_ok()).copied()
+    } else {
+        None
+    };
+    if let Some(button) = target {
+        if let Ok(mut interaction) = interaction_query.get_mut(button) {
+            *interaction = Interaction::Pressed;
+            *pressed_last_frame = Some(button);
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+// The active scope's buttons, in tree order
+fn focusable_buttons(
+    scopes: &FocusScopes,
+    children_query: &Query<&Children>,
+    button_query: &FocusableQuery
+) -> Vec<Entity> {
+    let scope = match scopes.0.last() {
+        Some(s) => *s,
+        None => return Vec::new(),
+    };
+    children_query
+        .iter_descendants_depth_first(scope)
+        .filter(|entity| button_query.get(*entity).is_ok())
+        .collect()
+}
+
+// UI coordinates grow downwards, so "up" is -y. Candidates are scored by distance in the
+// direction moved plus a penalty for sideways distance; buttons behind or level with the current
+// one are never picked.
+fn nearest_in_direction(
+    current: Entity,
+    direction: Vec2,
+    focusables: &[Entity],
+    transform_query: &Query<&UiGlobalTransform>
+) -> Option<Entity> {
+    let origin = transform_query.get(current).ok()?.translation;
+
+    focusables.iter()
+        .filter(|candidate| **candidate != current)
+        .filter_map(|candidate| {
+            let offset = transform_query.get(*candidate).ok()?.translation - origin;
+            let along = offset.dot(direction);
+            if along < 1.0 {
+                return None;
+            }
+            let across = offset.perp_dot(direction).abs();
+            Some((*candidate, along + across * PERPENDICULAR_WEIGHT))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(candidate, _)| candidate)
+}
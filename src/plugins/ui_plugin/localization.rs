@@ -0,0 +1,217 @@
+/// Per-language string tables for the text shown in menus.
+///
+/// Each language is a RON file in LOCALES_DIRPATH named by its language code (en.ron, de.ron, ...),
+/// mapping string keys ("menu.new") to the text shown. ExposedConfig.language picks the table. A key
+/// missing from it falls back to the FALLBACK_LANGUAGE table, and then to the key itself, so a
+/// missing translation shows as its key rather than as nothing.
+///
+/// Text built by the widgets carries a UiString saying where its text comes from. Localized text is
+/// filled in from the table, and filled in again whenever the language changes, so switching
+/// language also changes screens already shown. To change localized text later, set its UiString
+/// rather than its Text.
+///
+/// Action names ("Walk Forward") are not translated: they are the keys of the bindings in the config
+/// file, which has to work whatever the language. Their shown names are looked up under
+/// "action.<name>" instead.
+use std::{ collections::HashMap, fs };
+use bevy::prelude::*;
+
+use crate::{
+    plugins::exposed_config_plugin::ExposedConfig,
+    reflect_ron::from_ron_str,
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const LOCALES_DIRPATH: &str = "config/locales";
+pub const FALLBACK_LANGUAGE: &str = "en";
+const ACTION_KEY_PREFIX: &str = "action.";
+
+// Every table should have "language.name", the language's name for itself, shown in the language
+// setting
+#[derive(Reflect, Debug, Clone, Default)]
+pub struct StringTable {
+    pub strings: HashMap<String, String>
+}
+
+
+/////////////////////////////////////////
+// LOCALIZATION STATE
+
+#[derive(Resource, Debug, Default)]
+pub struct Localization {
+    language: String,
+    table: StringTable,
+    fallback: StringTable
+}
+
+impl Localization {
+    /// Reads the tables for language and FALLBACK_LANGUAGE. A table that can't be read is left empty.
+    pub fn load(language: &str) -> Self {
+        Localization {
+            language: String::from(language),
+            table: read_string_table(language),
+            fallback: match language == FALLBACK_LANGUAGE {
+                true => StringTable::default(),
+                false => read_string_table(FALLBACK_LANGUAGE),
+            }
+        }
+    }
+
+    /// An action missing from the tables shows as its name, rather than as "action.<name>"
+    pub fn get(&self, key: &str) -> String {
+        match self.lookup(key) {
+            Some(text) => text.to_string(),
+            None => key.strip_prefix(ACTION_KEY_PREFIX).unwrap_or(key).to_string(),
+        }
+    }
+
+    /// get, with "{0}", "{1}", ... replaced by args
+    pub fn format(&self, key: &str, args: &[String]) -> String {
+        args.iter()
+            .enumerate()
+            .fold(self.get(key), |text, (index, arg)| text.replace(&format!("{{{}}}", index), arg))
+    }
+
+    fn lookup(&self, key: &str) -> Option<&str> {
+        self.table.strings.get(key)
+            .or_else(|| self.fallback.strings.get(key))
+            .map(String::as_str)
+    }
+}
+
+/// Where a text node's text comes from. Added by the text widgets.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub enum UiString {
+    /// Shown as written - names, save data and other text that isn't translated
+    Literal(String),
+    /// Looked up in the string table, with "{0}", "{1}", ... replaced by args (which may be keys
+    /// themselves, e.g. a stat name)
+    Localized { key: String, args: Vec<UiString> }
+}
+
+impl UiString {
+    pub fn key(key: impl Into<String>) -> Self {
+        UiString::Localized { key: key.into(), args: Vec::new() }
+    }
+
+    /// The shown name of a bound action
+    pub fn action(action: &str) -> Self {
+        UiString::key(format!("{}{}", ACTION_KEY_PREFIX, action))
+    }
+
+    pub fn key_with<T: Into<UiString>>(key: impl Into<String>, args: impl IntoIterator<Item = T>) -> Self {
+        UiString::Localized {
+            key: key.into(),
+            args: args.into_iter().map(Into::into).collect()
+        }
+    }
+
+    pub fn resolve(&self, localization: &Localization) -> String {
+        match self {
+            UiString::Literal(text) => text.clone(),
+            UiString::Localized { key, args } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.resolve(localization)).collect();
+                localization.format(key, &args)
+            }
+        }
+    }
+}
+
+impl From<&str> for UiString {
+    fn from(text: &str) -> Self {
+        UiString::Literal(String::from(text))
+    }
+}
+
+impl From<String> for UiString {
+    fn from(text: String) -> Self {
+        UiString::Literal(text)
+    }
+}
+
+impl From<u32> for UiString {
+    fn from(number: u32) -> Self {
+        UiString::Literal(number.to_string())
+    }
+}
+
+impl From<&String> for UiString {
+    fn from(text: &String) -> Self {
+        UiString::Literal(text.clone())
+    }
+}
+
+/// The language codes that have a string table, in alphabetical order
+pub fn available_languages() -> Vec<String> {
+    let entries = match fs::read_dir(LOCALES_DIRPATH) {
+        Ok(e) => e,
+        Err(_) => return vec![String::from(FALLBACK_LANGUAGE)],
+    };
+    let mut languages: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            match path.extension()?.to_str()? {
+                "ron" => Some(path.file_stem()?.to_str()?.to_string()),
+                _ => None,
+            }
+        })
+        .collect();
+    languages.sort();
+    languages
+}
+
+
+/////////////////////////////////////////
+// SYSTEMS
+
+pub(super) fn sync_language(
+    exposed_config: Option<Res<ExposedConfig>>,
+    mut localization: ResMut<Localization>
+) {
+    let exposed_config = match exposed_config {
+        Some(c) => c,
+        None => return,
+    };
+    if exposed_config.is_changed() && exposed_config.language != localization.language {
+        *localization = Localization::load(&exposed_config.language);
+    }
+}
+
+/// Fills in text whose UiString is new or changed, and all localized text when the language changes.
+/// Literal text is otherwise left alone, so screens that write to Text directly (e.g. the dialogue
+/// typewriter) keep working.
+pub(super) fn resolve_ui_strings(
+    localization: Res<Localization>,
+    mut text_query: Query<(Ref<UiString>, &mut Text)>
+) {
+    for (ui_string, mut text) in &mut text_query {
+        let relocalize = localization.is_changed() && matches!(*ui_string, UiString::Localized { .. });
+        if !ui_string.is_changed() && !relocalize {
+            continue;
+        }
+        let resolved = ui_string.resolve(&localization);
+        if text.0 != resolved {
+            text.0 = resolved;
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn read_string_table(language: &str) -> StringTable {
+    let filepath = format!("{}/{}.ron", LOCALES_DIRPATH, language);
+    let read = fs::read_to_string(&filepath)
+        .map_err(|e| e.to_string())
+        .and_then(|ron_str| from_ron_str(&ron_str));
+    match read {
+        Ok(t) => t,
+        Err(e) => {
+            error!("failure loading string table {}: {}", filepath, e);
+            StringTable::default()
+        }
+    }
+}
@@ -0,0 +1,124 @@
+/// The UI theme: every colour, font size, size and border used by the widgets in widgets.rs.
+///
+/// The theme is read from THEME_FILEPATH at startup, so menus can be re-themed by editing that file.
+/// If it can't be read, UiTheme::default() (which matches the shipped file) is used instead.
+use std::fs;
+use bevy::prelude::*;
+
+use crate::reflect_ron::from_ron_str;
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+const THEME_FILEPATH: &str = "config/ui_theme.ron";
+
+#[derive(Reflect, Resource, Debug, Clone)]
+pub struct UiTheme {
+    pub colors: ThemeColors,
+    pub fonts: ThemeFonts,
+    pub sizes: ThemeSizes
+}
+
+#[derive(Reflect, Debug, Clone)]
+pub struct ThemeColors {
+    pub text: Color,
+    pub title_text: Color,
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub button_selected: Color,
+    pub button_border: Color,
+    pub button_border_hovered: Color,
+    pub button_border_selected: Color,
+    pub button_border_focused: Color,
+    pub screen_background: Color,
+    pub panel_background: Color,
+    pub overlay_background: Color,
+    pub placeholder: Color
+}
+
+// path is relative to the assets folder; None uses Bevy's built in font.
+// font_handle is filled in from path when the theme is loaded.
+#[derive(Reflect, Debug, Clone)]
+pub struct ThemeFonts {
+    #[reflect(default)]
+    pub path: Option<String>,
+    pub title_size: f32,
+    pub button_size: f32,
+    pub body_size: f32,
+    pub small_size: f32,
+    #[reflect(ignore)]
+    pub font_handle: Handle<Font>
+}
+
+// all in logical pixels
+#[derive(Reflect, Debug, Clone)]
+pub struct ThemeSizes {
+    pub button_width: f32,
+    pub button_height: f32,
+    pub row_height: f32,
+    pub border_width: f32,
+    pub padding: f32,
+    pub gap: f32
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        UiTheme {
+            colors: ThemeColors {
+                text: Color::srgb(0.9, 0.9, 0.9),
+                title_text: Color::srgb(0.95, 0.85, 0.4),
+                button: Color::srgb(0.15, 0.15, 0.15),
+                button_hovered: Color::srgb(0.25, 0.45, 0.25),
+                button_pressed: Color::srgb(0.35, 0.75, 0.35),
+                button_selected: Color::srgb(0.35, 0.75, 0.35),
+                button_border: Color::srgb(0.0, 0.0, 0.0),
+                button_border_hovered: Color::srgb(1.0, 0.0, 0.0),
+                button_border_selected: Color::srgb(1.0, 1.0, 1.0),
+                button_border_focused: Color::srgb(0.95, 0.85, 0.4),
+                screen_background: Color::srgb(0.0, 0.0, 0.0),
+                panel_background: Color::srgba(0.05, 0.05, 0.1, 0.9),
+                overlay_background: Color::srgba(0.0, 0.0, 0.0, 0.6),
+                placeholder: Color::srgb(0.9, 0.05, 0.05)
+            },
+            fonts: ThemeFonts {
+                path: None,
+                title_size: 36.,
+                button_size: 24.,
+                body_size: 20.,
+                small_size: 14.,
+                font_handle: Handle::default()
+            },
+            sizes: ThemeSizes {
+                button_width: 320.,
+                button_height: 65.,
+                row_height: 60.,
+                border_width: 2.,
+                padding: 20.,
+                gap: 10.
+            }
+        }
+    }
+}
+
+
+/// The font is only loaded if an AssetServer is given; otherwise Bevy's built in font is used
+pub fn load_ui_theme(asset_server: Option<&AssetServer>) -> UiTheme {
+    let mut theme = match read_theme_file(THEME_FILEPATH) {
+        Ok(t) => t,
+        Err(e) => {
+            error!("failure loading UI theme, using default: {}", e);
+            UiTheme::default()
+        }
+    };
+
+    if let (Some(path), Some(asset_server)) = (&theme.fonts.path, asset_server) {
+        theme.fonts.font_handle = asset_server.load(path);
+    }
+    theme
+}
+
+fn read_theme_file(filepath: &str) -> Result<UiTheme, String> {
+    let theme_ron_str = fs::read_to_string(filepath).map_err(|e| format!("{}: {}", filepath, e))?;
+    from_ron_str(&theme_ron_str).map_err(|e| format!("{}: {}", filepath, e))
+}
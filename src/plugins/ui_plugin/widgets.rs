@@ -0,0 +1,247 @@
+/// Builders for the pieces menus are made of - screen roots, overlays, panels, lists, buttons and
+/// text - all sized and coloured from UiTheme, so every screen looks the same.
+///
+/// Buttons built here carry ThemedButton and are restyled by style_themed_buttons; a screen only
+/// has to react to their Interaction. Adding UiSelected to a button keeps it highlighted (e.g. the
+/// selected tab or save row). The roots are FocusScopes, so their buttons can be navigated without
+/// a mouse (see focus.rs). Text takes a UiString, so it can be a localization key (see
+/// localization.rs) or shown as written.
+use bevy::prelude::*;
+
+use crate::plugins::ui_plugin::{
+    focus::{ FocusScope, UiFocus },
+    localization::UiString,
+    theme::UiTheme,
+};
+
+
+/////////////////////////////////////////
+// BUTTON STATE
+
+#[derive(Component, Default)]
+#[require(Button)]
+pub struct ThemedButton;
+
+#[derive(Component)]
+pub struct UiSelected;
+
+/// Pressed takes priority over selected, which takes priority over hovered. A focused button is
+/// drawn as hovered, with its own border colour.
+pub fn style_themed_buttons(
+    theme: Res<UiTheme>,
+    focus: Res<UiFocus>,
+    mut button_query: Query<
+        (Entity, &Interaction, Has<UiSelected>, &mut BackgroundColor, &mut BorderColor),
+        With<ThemedButton>
+    >,
+) {
+    let colors = &theme.colors;
+    for (button, interaction, selected, mut background_color, mut border_color) in &mut button_query {
+        let focused = focus.visible && focus.entity == Some(button);
+        let interaction = match *interaction {
+            Interaction::None if focused => Interaction::Hovered,
+            i => i
+        };
+        let background = match (interaction, selected) {
+            (Interaction::Pressed, _) => colors.button_pressed,
+            (_, true) => colors.button_selected,
+            (Interaction::Hovered, false) => colors.button_hovered,
+            (Interaction::None, false) => colors.button,
+        };
+        let border = match (interaction, selected) {
+            _ if focused => colors.button_border_focused,
+            (_, true) => colors.button_border_selected,
+            (Interaction::None, false) => colors.button_border,
+            (_, false) => colors.button_border_hovered,
+        };
+
+        if background_color.0 != background {
+            background_color.0 = background;
+        }
+        if border_color.top != border {
+            border_color.set_all(border);
+        }
+    }
+}
+
+
+/////////////////////////////////////////
+// CONTAINERS
+
+/// Full screen, content centred in a column, drawn by ui_camera
+pub fn screen_root(theme: &UiTheme, ui_camera: Entity) -> impl Bundle {
+    (
+        centred_column(theme, Val::Percent(100.0), Val::Percent(100.0)),
+        BackgroundColor(theme.colors.screen_background),
+        UiTargetCamera(ui_camera),
+        FocusScope
+    )
+}
+
+/// Like screen_root, but see-through so the scene (or screen) underneath still shows
+pub fn overlay_root(theme: &UiTheme, ui_camera: Entity) -> impl Bundle {
+    (
+        centred_column(theme, Val::Percent(100.0), Val::Percent(100.0)),
+        BackgroundColor(theme.colors.overlay_background),
+        UiTargetCamera(ui_camera),
+        FocusScope
+    )
+}
+
+/// An overlay with a single panel in the middle. panel_contents is added to the panel - usually a
+/// marker component, so the panel can be found and filled later.
+pub fn modal(theme: &UiTheme, ui_camera: Entity, panel_contents: impl Bundle) -> impl Bundle {
+    (
+        overlay_root(theme, ui_camera),
+        children![(panel(theme), panel_contents)]
+    )
+}
+
+pub fn panel(theme: &UiTheme) -> impl Bundle {
+    (
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(Val::Px(theme.sizes.padding)),
+            row_gap: Val::Px(theme.sizes.gap),
+            ..default()
+        },
+        BackgroundColor(theme.colors.panel_background)
+    )
+}
+
+/// A column of rows (see list_row), stretched to its parent's width
+pub fn list(theme: &UiTheme) -> Node {
+    Node {
+        width: Val::Percent(100.0),
+        flex_direction: FlexDirection::Column,
+        row_gap: Val::Px(theme.sizes.gap),
+        ..default()
+    }
+}
+
+/// A horizontal group of buttons
+pub fn button_row(theme: &UiTheme) -> Node {
+    Node {
+        flex_direction: FlexDirection::Row,
+        column_gap: Val::Px(theme.sizes.gap),
+        ..default()
+    }
+}
+
+/// Also useful as a single page within a panel
+pub fn column(theme: &UiTheme) -> Node {
+    Node {
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        row_gap: Val::Px(theme.sizes.gap),
+        ..default()
+    }
+}
+
+
+/////////////////////////////////////////
+// BUTTONS
+
+/// A fixed size button with a text label
+pub fn button(theme: &UiTheme, text: impl Into<UiString>) -> impl Bundle {
+    (
+        ThemedButton,
+        Node {
+            width: Val::Px(theme.sizes.button_width),
+            height: Val::Px(theme.sizes.button_height),
+            border: UiRect::all(Val::Px(theme.sizes.border_width)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(theme.colors.button),
+        BorderColor::all(theme.colors.button_border),
+        children![button_text(theme, text)]
+    )
+}
+
+/// A button sized to its label, for tabs and other buttons sitting in a row
+pub fn compact_button(theme: &UiTheme, text: impl Into<UiString>) -> impl Bundle {
+    (
+        ThemedButton,
+        Node {
+            padding: UiRect::axes(Val::Px(theme.sizes.padding * 0.75), Val::Px(theme.sizes.padding * 0.4)),
+            border: UiRect::all(Val::Px(theme.sizes.border_width)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(theme.colors.button),
+        BorderColor::all(theme.colors.button_border),
+        children![button_text(theme, text)]
+    )
+}
+
+/// A full width selectable row within a list. Add the row's contents as children.
+pub fn list_row(theme: &UiTheme) -> impl Bundle {
+    (
+        ThemedButton,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Px(theme.sizes.row_height),
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Start,
+            border: UiRect::all(Val::Px(theme.sizes.border_width)),
+            ..default()
+        },
+        BackgroundColor(theme.colors.button),
+        BorderColor::all(theme.colors.button_border)
+    )
+}
+
+
+/////////////////////////////////////////
+// TEXT
+
+pub fn title_text(theme: &UiTheme, text: impl Into<UiString>) -> impl Bundle {
+    themed_text(theme, text, theme.fonts.title_size, theme.colors.title_text)
+}
+
+pub fn body_text(theme: &UiTheme, text: impl Into<UiString>) -> impl Bundle {
+    themed_text(theme, text, theme.fonts.body_size, theme.colors.text)
+}
+
+pub fn small_text(theme: &UiTheme, text: impl Into<UiString>) -> impl Bundle {
+    themed_text(theme, text, theme.fonts.small_size, theme.colors.text)
+}
+
+pub fn button_text(theme: &UiTheme, text: impl Into<UiString>) -> impl Bundle {
+    themed_text(theme, text, theme.fonts.button_size, theme.colors.text)
+}
+
+// Localized text starts empty and is filled in by resolve_ui_strings before the UI is laid out
+fn themed_text(theme: &UiTheme, text: impl Into<UiString>, font_size: f32, color: Color) -> impl Bundle {
+    let ui_string = text.into();
+    (
+        Text::new(match &ui_string {
+            UiString::Literal(text) => text.clone(),
+            UiString::Localized { .. } => String::new(),
+        }),
+        ui_string,
+        TextFont {
+            font: theme.fonts.font_handle.clone(),
+            font_size,
+            ..default()
+        },
+        TextColor(color)
+    )
+}
+
+fn centred_column(theme: &UiTheme, width: Val, height: Val) -> Node {
+    Node {
+        width,
+        height,
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        row_gap: Val::Px(theme.sizes.gap),
+        ..default()
+    }
+}
@@ -0,0 +1,68 @@
+// Helpers for reading and writing RON files through Bevy's Reflect trait, rather than serde
+// derives. This is how ExposedConfig has been (de)serialized from the start; these functions let
+// other data files (saves, maps) go through the same path.
+// https://taintedcoders.com/bevy/reflection
+
+use std::any::TypeId;
+use serde::de::DeserializeSeed;
+use bevy::reflect::{
+    FromReflect, GetTypeRegistration, PartialReflect, Reflect, TypeRegistry,
+    serde::{ TypedReflectDeserializer, TypedReflectSerializer }
+};
+
+
+/// Deserialize a RON string into T. T's type (and the types of its fields) are registered in a
+/// throwaway TypeRegistry so the deserializer knows their shape.
+pub fn from_ron_str<T>(ron_str: &str) -> Result<T, String>
+where
+    T: FromReflect + GetTypeRegistration
+{
+    let mut type_registry = TypeRegistry::default();
+    type_registry.register::<T>();
+
+    let registration = type_registry
+        .get(TypeId::of::<T>())
+        .ok_or_else(|| String::from("type missing from registry"))?;
+    let mut deserializer = ron::de::Deserializer::from_str(ron_str).map_err(|e| e.to_string())?;
+    let reflect_deserializer = TypedReflectDeserializer::new(registration, &type_registry);
+    let reflect_box: Box<dyn PartialReflect> = reflect_deserializer
+        .deserialize(&mut deserializer)
+        .map_err(|e| e.to_string())?;
+
+    T::from_reflect(&*reflect_box).ok_or_else(|| String::from("could not convert from reflected value"))
+}
+
+/// Serialize T into a pretty-printed RON string
+pub fn to_ron_string<T>(value: &T) -> Result<String, String>
+where
+    T: Reflect + GetTypeRegistration
+{
+    let mut type_registry = TypeRegistry::default();
+    type_registry.register::<T>();
+
+    let reflect_serializer = TypedReflectSerializer::new(value.as_partial_reflect(), &type_registry);
+    ron::ser::to_string_pretty(&reflect_serializer, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())
+}
+
+/// Deserialize a RON string into a value of a type registered in type_registry, e.g. one field of
+/// a larger reflected value (see the developer console's set command)
+pub fn value_from_ron_str(
+    type_id: TypeId,
+    ron_str: &str,
+    type_registry: &TypeRegistry
+) -> Result<Box<dyn PartialReflect>, String> {
+    let registration = type_registry
+        .get(type_id)
+        .ok_or_else(|| String::from("type missing from registry"))?;
+    let mut deserializer = ron::de::Deserializer::from_str(ron_str).map_err(|e| e.to_string())?;
+    TypedReflectDeserializer::new(registration, type_registry)
+        .deserialize(&mut deserializer)
+        .map_err(|e| e.to_string())
+}
+
+/// Serialize a value of a type registered in type_registry into a one line RON string
+pub fn value_to_ron_string(value: &dyn PartialReflect, type_registry: &TypeRegistry) -> Result<String, String> {
+    let reflect_serializer = TypedReflectSerializer::new(value, type_registry);
+    ron::ser::to_string(&reflect_serializer).map_err(|e| e.to_string())
+}
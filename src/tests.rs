@@ -0,0 +1,9 @@
+// Headless tests, run with cargo test. The game's plugins are built into an App with
+// MinimalPlugins rather than DefaultPlugins, so there is no window, renderer or GPU: input is faked
+// through ButtonInput and time is stepped a fixed amount per frame (see harness.rs).
+
+mod config;
+mod harness;
+mod movement;
+mod recording;
+mod state_flow;
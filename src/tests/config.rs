@@ -0,0 +1,35 @@
+// Checks on config/game_config.ron that a typo or a rebinding could otherwise break quietly.
+
+use bevy::prelude::*;
+
+use crate::plugins::exposed_config_plugin::{ read_exposed_config_file, shared_bindings };
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+
+const CONFIG_FILEPATH: &str = "config/game_config.ron";
+
+
+/////////////////////////////////////////
+// TESTS
+
+#[test]
+fn the_default_bindings_share_no_key_or_button_within_a_context() {
+    let config = read_exposed_config_file(CONFIG_FILEPATH).expect("failure reading the config");
+    assert_eq!(shared_bindings(&config), Vec::<String>::new());
+}
+
+#[test]
+fn binding_one_button_to_two_actions_read_together_is_reported() {
+    let mut config = read_exposed_config_file(CONFIG_FILEPATH).expect("failure reading the config");
+    // "Walk Forward" is on DPadUp too, and both are read in Explore and in dialogue
+    config.controller_bindings.exploration_controls.insert(String::from("Interact"), GamepadButton::DPadUp);
+    assert_eq!(
+        shared_bindings(&config),
+        [
+            "button DPadUp is bound to both Walk Forward and Interact in Explore",
+            "button DPadUp is bound to both Walk Forward and Interact in Dialogue"
+        ]
+    );
+}
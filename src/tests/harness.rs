@@ -0,0 +1,318 @@
+/// A game built without a window or renderer, for tests to play.
+///
+/// TestGame adds DcrawlPlugins, as main.rs does, but on top of MinimalPlugins instead of
+/// DefaultPlugins, and with the asset types those plugins use registered by hand. Each update() is
+/// one frame of frame_time(), which is the fixed timestep - so every frame runs FixedUpdate (and so
+/// the movement queue) exactly once, and a test's outcome doesn't depend on how fast it ran.
+///
+/// Keys are pressed by writing KeyboardInput messages, which InputPlugin turns into ButtonInput at
+/// the start of the next frame, exactly as window input would be. Menu buttons are pressed by
+/// setting their Interaction, as Bevy's UI picking would.
+///
+/// Saves written while testing (e.g. autosaves on entering a map) go to a directory of the game's
+/// own, emptied when the game is built and removed when it is dropped - not to the player's saves,
+/// nor where another test running alongside would see them (see save_plugin::SavesDir).
+use std::{ fs, sync::atomic::{ AtomicU32, Ordering }, time::Duration };
+use bevy::{
+    prelude::*,
+    input::{ ButtonState, InputPlugin, keyboard::{ Key, KeyboardInput, NativeKey }, mouse::MouseMotion },
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+    window::CursorMoved,
+};
+
+use crate::{
+    DcrawlPlugins,
+    launch_options::{ LaunchOptions, LaunchState },
+    plugins::{
+        camera_plugin::NavigateCamera,
+        explore_plugin::{
+            map::{ CurrentMap, Grid, current_grid },
+            movement::{ CardinalDirection, ExplorationMovementData },
+            recording::ExplorationReplay,
+        },
+        exposed_config_plugin::ExposedConfig,
+        manage_state_plugin::{ GameModeState, InGameSubstate },
+        save_plugin::SavesDir,
+        ui_plugin::localization::UiString,
+    },
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+
+// Give up on run_until after this many frames (30 seconds of play)
+const MAX_FRAMES: u32 = 64 * 30;
+
+// Each TestGame saves to a numbered directory in here
+const TEST_SAVES_DIRPATH: &str = "target/test_saves";
+static NEXT_SAVES_DIR: AtomicU32 = AtomicU32::new(0);
+
+// How far the camera may be from where a test expects it, after a movement finishes
+pub const POSITION_TOLERANCE: f32 = 1e-3;
+pub const ROTATION_TOLERANCE: f32 = 1e-3;
+
+
+/////////////////////////////////////////
+// TEST GAME
+
+pub struct TestGame {
+    pub app: App
+}
+
+impl TestGame {
+    /// Builds the game and runs its first frame (Startup, and the switch to the launch state)
+    pub fn new(launch_options: LaunchOptions) -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin, InputPlugin));
+        // registered by the render, PBR and text plugins in a real build
+        app.init_asset::<Image>();
+        app.init_asset::<Mesh>();
+        app.init_asset::<StandardMaterial>();
+        app.init_asset::<Font>();
+        // registered by WindowPlugin in a real build
+        app.add_message::<CursorMoved>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame_time()));
+
+        let saves_dir = format!("{}/game_{}", TEST_SAVES_DIRPATH, NEXT_SAVES_DIR.fetch_add(1, Ordering::Relaxed));
+        // left over from an earlier run
+        let _ = fs::remove_dir_all(&saves_dir);
+
+        app
+            .insert_resource(SavesDir(saves_dir))
+            .insert_resource(launch_options)
+            .add_plugins(DcrawlPlugins);
+
+        let mut game = TestGame { app };
+        game.update();
+        game
+    }
+
+    /// A new game on the default map, in Explore and ready for movement input
+    pub fn in_game() -> Self {
+        TestGame::in_game_with(LaunchOptions::default())
+    }
+
+    /// A new game started with launch_options (e.g. its pos and facing), in Explore
+    pub fn in_game_with(launch_options: LaunchOptions) -> Self {
+        let mut game = TestGame::new(LaunchOptions {
+            state: Some(LaunchState::InGame),
+            ..launch_options
+        });
+        game.run_until("Explore is entered", |game| game.ingame_substate() == Some(InGameSubstate::Explore));
+        // let OnEnter's commands apply
+        game.update();
+        game
+    }
+
+    /// Makes each following frame frame_time long instead of one fixed timestep, so that FixedUpdate
+    /// runs in only some frames, or several times in one
+    pub fn set_frame_time(&mut self, frame_time: Duration) {
+        self.app.insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+    }
+
+    pub fn update(&mut self) {
+        self.app.update();
+    }
+
+    pub fn run_frames(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.update();
+        }
+    }
+
+    pub fn run_secs(&mut self, secs: f32) {
+        self.run_frames((secs / frame_time().as_secs_f32()).ceil() as u32);
+    }
+
+    /// Runs frames until condition holds, panicking (naming what was waited for) after MAX_FRAMES
+    pub fn run_until(&mut self, waiting_for: &str, condition: impl Fn(&TestGame) -> bool) {
+        for _ in 0..MAX_FRAMES {
+            if condition(self) {
+                return;
+            }
+            self.update();
+        }
+        panic!("gave up after {} frames waiting until {}", MAX_FRAMES, waiting_for);
+    }
+
+    /// Runs frames until every buffered and queued movement has finished
+    pub fn finish_movement(&mut self) {
+        self.run_until("the movement queue is empty", |game| {
+            let movement_data = game.movement_data();
+            movement_data.input_buffer.is_empty()
+                && movement_data.command_queue.is_empty()
+                && movement_data.current_movement_timer.is_none()
+        });
+    }
+
+    /// Runs frames until the recording being replayed (see LaunchOptions.replay) has played back,
+    /// and its last movement has finished
+    pub fn finish_replay(&mut self) {
+        self.run_until("the replay has played back", |game| game.world().resource::<ExplorationReplay>().finished());
+        self.finish_movement();
+    }
+
+
+    /////////////////////////////////////////
+    // INPUT
+
+    pub fn press(&mut self, key: KeyCode) {
+        self.write_key(key, ButtonState::Pressed);
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.write_key(key, ButtonState::Released);
+    }
+
+    /// Presses and releases key within one frame, and runs that frame
+    pub fn tap(&mut self, key: KeyCode) {
+        self.press(key);
+        self.release(key);
+        self.update();
+    }
+
+    /// Moves the mouse by delta pixels (x right, y down) during the next frame
+    pub fn move_mouse(&mut self, delta: Vec2) {
+        self.world_mut().write_message(MouseMotion { delta });
+    }
+
+    /// The key bound to an exploration action in ExposedConfig, e.g. "Walk Forward"
+    pub fn binding(&self, action: &str) -> KeyCode {
+        match self.world().resource::<ExposedConfig>().keyboard_bindings.exploration_controls.get(action) {
+            Some(key) => *key,
+            None => panic!("no keyboard binding for {}", action),
+        }
+    }
+
+    pub fn tap_action(&mut self, action: &str) {
+        self.tap(self.binding(action));
+    }
+
+    /// Presses the button labelled with the localization key label_key (e.g. "menu.load") for one
+    /// frame, panicking if no such button is shown
+    pub fn press_button(&mut self, label_key: &str) {
+        let button = match self.find_button(label_key) {
+            Some(b) => b,
+            None => panic!("no button labelled {} is shown", label_key),
+        };
+        self.world_mut().entity_mut(button).insert(Interaction::Pressed);
+        self.update();
+        if let Ok(mut button) = self.world_mut().get_entity_mut(button) {
+            button.insert(Interaction::None);
+        }
+    }
+
+    pub fn find_button(&self, label_key: &str) -> Option<Entity> {
+        let world = self.world();
+        let labelled = |entity: Entity| match world.get::<UiString>(entity) {
+            Some(UiString::Localized { key, .. }) => key == label_key,
+            _ => false,
+        };
+        let mut button_query = world.try_query_filtered::<(Entity, &Children), With<Button>>()?;
+        button_query
+            .iter(world)
+            .find(|(_, children)| children.iter().any(labelled))
+            .map(|(button, _)| button)
+    }
+
+
+    /////////////////////////////////////////
+    // INSPECTION
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn game_mode(&self) -> GameModeState {
+        self.world().resource::<State<GameModeState>>().get().clone()
+    }
+
+    /// None outside of InGame
+    pub fn ingame_substate(&self) -> Option<InGameSubstate> {
+        self.world()
+            .get_resource::<State<InGameSubstate>>()
+            .map(|state| state.get().clone())
+    }
+
+    pub fn camera_transform(&self) -> Transform {
+        let world = self.world();
+        let mut camera_query = world
+            .try_query_filtered::<&Transform, With<NavigateCamera>>()
+            .expect("no NavigateCamera");
+        *camera_query.single(world).expect("no NavigateCamera")
+    }
+
+    /// Moves the camera without going through the movement queue
+    pub fn set_camera_transform(&mut self, transform: Transform) {
+        let world = self.world_mut();
+        let mut camera_query = world.query_filtered::<&mut Transform, With<NavigateCamera>>();
+        *camera_query.single_mut(world).expect("no NavigateCamera") = transform;
+    }
+
+    /// (row, column) of the cell the camera is on
+    pub fn cell(&self) -> (i32, i32) {
+        self.grid().world_to_cell(self.camera_transform().translation)
+    }
+
+    /// The grid of the current map
+    pub fn grid(&self) -> Grid {
+        current_grid(self.world().get_resource::<CurrentMap>())
+    }
+
+    pub fn facing(&self) -> Option<CardinalDirection> {
+        self.movement_data().cardinal_facing
+    }
+
+    pub fn movement_data(&self) -> &ExplorationMovementData {
+        self.world().resource::<ExplorationMovementData>()
+    }
+
+    /// Where this game's saves are written
+    pub fn saves_dir(&self) -> &SavesDir {
+        self.world().resource::<SavesDir>()
+    }
+}
+
+impl Drop for TestGame {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.saves_dir().0);
+    }
+}
+
+/// Each frame is one fixed timestep long
+pub fn frame_time() -> Duration {
+    Time::<Fixed>::default().timestep()
+}
+
+/// The camera's rotation about y, in radians
+pub fn yaw(transform: &Transform) -> f32 {
+    transform.rotation.to_euler(EulerRot::YXZ).0
+}
+
+/// How far the camera is tilted up (positive) or down, in radians
+pub fn pitch(transform: &Transform) -> f32 {
+    transform.rotation.to_euler(EulerRot::YXZ).1
+}
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+impl TestGame {
+    fn write_key(&mut self, key: KeyCode, state: ButtonState) {
+        self.world_mut().write_message(KeyboardInput {
+            key_code: key,
+            logical_key: Key::Unidentified(NativeKey::Unidentified),
+            state,
+            text: None,
+            repeat: false,
+            window: Entity::PLACEHOLDER
+        });
+    }
+}
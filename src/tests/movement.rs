@@ -0,0 +1,470 @@
+// Grid movement in Explore: each step or turn should end exactly on a cell or cardinal direction,
+// however the frames fall, and one press should only ever move once.
+
+use std::{ f32::consts::{ FRAC_PI_2, FRAC_PI_4 }, time::Duration };
+use bevy::prelude::*;
+
+use crate::{
+    launch_options::LaunchOptions,
+    plugins::{
+        explore_plugin::{
+            continuous::{ MovementMode, PLAYER_RADIUS },
+            map::{ CurrentMap, Grid },
+            movement::{ CardinalDirection, DirectionSet },
+        },
+        exposed_config_plugin::ExposedConfig,
+        manage_state_plugin::InGameSubstate,
+    },
+    tests::harness::{ POSITION_TOLERANCE, ROTATION_TOLERANCE, TestGame, pitch, yaw },
+};
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+// Away from the test map's triggers and NPC, so nothing interrupts the moves
+const START_CELL: (i32, i32) = (2, 3);
+
+fn game_facing_north() -> TestGame {
+    TestGame::in_game_with(LaunchOptions {
+        pos: Some(START_CELL),
+        facing: Some(CardinalDirection::North),
+        ..default()
+    })
+}
+
+fn switch_to_continuous_movement(game: &mut TestGame) {
+    game.world_mut().resource_mut::<ExposedConfig>().movement.mode = MovementMode::Continuous;
+    game.update();
+    assert!(game.movement_data().moving_continuously);
+}
+
+// The test map laid out on a smaller, eight-way grid, with the camera moved onto it
+fn switch_to_eight_way_grid(game: &mut TestGame, cell_size: f32) {
+    let mut current_map = game.world_mut().resource_mut::<CurrentMap>();
+    current_map.definition.grid = Grid { cell_size, directions: DirectionSet::EightWay };
+    let mut camera_transform = game.camera_transform();
+    camera_transform.translation = game.grid().cell_to_world(START_CELL, camera_transform.translation.y);
+    game.set_camera_transform(camera_transform);
+}
+
+fn move_and_finish(game: &mut TestGame, action: &str) {
+    game.tap_action(action);
+    game.finish_movement();
+}
+
+fn assert_on_cell(game: &TestGame, cell: (i32, i32)) {
+    let translation = game.camera_transform().translation;
+    let expected = game.grid().cell_to_world(cell, translation.y);
+    assert_eq!(game.cell(), cell);
+    assert!(
+        translation.distance(expected) < POSITION_TOLERANCE,
+        "camera at {} rather than the centre of {:?}, {}", translation, cell, expected
+    );
+}
+
+fn assert_facing(game: &TestGame, facing: CardinalDirection, expected_yaw: f32) {
+    let rotation = game.camera_transform().rotation;
+    assert_eq!(game.facing(), Some(facing));
+    assert!(
+        rotation.angle_between(Quat::from_rotation_y(expected_yaw)) < ROTATION_TOLERANCE,
+        "camera yaw {} rather than {}", yaw(&game.camera_transform()), expected_yaw
+    );
+}
+
+
+/////////////////////////////////////////
+// TESTS
+
+#[test]
+fn starts_where_launch_options_say() {
+    let game = game_facing_north();
+    assert_on_cell(&game, START_CELL);
+    assert_facing(&game, CardinalDirection::North, 0.);
+}
+
+#[test]
+fn walking_and_strafing_move_one_cell() {
+    let mut game = game_facing_north();
+
+    move_and_finish(&mut game, "Walk Forward");
+    assert_on_cell(&game, (1, 3));
+    move_and_finish(&mut game, "Strafe Right");
+    assert_on_cell(&game, (1, 4));
+    move_and_finish(&mut game, "Walk Backward");
+    assert_on_cell(&game, (2, 4));
+    move_and_finish(&mut game, "Strafe Left");
+    assert_on_cell(&game, (2, 3));
+    assert_facing(&game, CardinalDirection::North, 0.);
+}
+
+#[test]
+fn turning_faces_the_next_cardinal_direction() {
+    let mut game = game_facing_north();
+
+    move_and_finish(&mut game, "Turn Right");
+    assert_facing(&game, CardinalDirection::East, -FRAC_PI_2);
+    move_and_finish(&mut game, "Turn Left");
+    move_and_finish(&mut game, "Turn Left");
+    assert_facing(&game, CardinalDirection::West, FRAC_PI_2);
+    assert_on_cell(&game, START_CELL);
+}
+
+#[test]
+fn four_turns_come_back_around() {
+    let mut game = game_facing_north();
+    for _ in 0..4 {
+        move_and_finish(&mut game, "Turn Right");
+    }
+    assert_facing(&game, CardinalDirection::North, 0.);
+}
+
+#[test]
+fn turning_never_overshoots() {
+    let mut game = game_facing_north();
+    game.tap_action("Turn Right");
+
+    let mut last_yaw = 0.;
+    while !game.movement_data().command_queue.is_empty() {
+        game.update();
+        let current_yaw = yaw(&game.camera_transform());
+        assert!(current_yaw <= last_yaw + ROTATION_TOLERANCE, "turned back, from {} to {}", last_yaw, current_yaw);
+        assert!(current_yaw >= -FRAC_PI_2 - ROTATION_TOLERANCE, "turned past east, to {}", current_yaw);
+        last_yaw = current_yaw;
+    }
+    assert_facing(&game, CardinalDirection::East, -FRAC_PI_2);
+}
+
+#[test]
+fn walking_after_a_turn_follows_the_new_facing() {
+    let mut game = game_facing_north();
+
+    move_and_finish(&mut game, "Turn Right");
+    move_and_finish(&mut game, "Walk Forward");
+    assert_on_cell(&game, (2, 4));
+    move_and_finish(&mut game, "Turn Right");
+    move_and_finish(&mut game, "Walk Forward");
+    assert_on_cell(&game, (3, 4));
+}
+
+#[test]
+fn holding_a_key_walks_on_until_the_way_is_blocked() {
+    let mut game = game_facing_north();
+    let walk_forward = game.binding("Walk Forward");
+
+    // (0, 3) is the test map's northmost cell in this column
+    game.press(walk_forward);
+    game.run_secs(5.);
+    assert_on_cell(&game, (0, 3));
+    assert!(game.movement_data().held_movement.is_none());
+    game.release(walk_forward);
+    game.finish_movement();
+    assert_on_cell(&game, (0, 3));
+}
+
+#[test]
+fn a_single_step_onto_a_blocked_cell_is_dropped() {
+    let mut game = TestGame::in_game_with(LaunchOptions {
+        pos: Some((0, 3)),
+        facing: Some(CardinalDirection::North),
+        ..default()
+    });
+
+    // north of (0, 3) is off the map, west of it an empty tile - but east is open
+    move_and_finish(&mut game, "Walk Forward");
+    assert_on_cell(&game, (0, 3));
+    move_and_finish(&mut game, "Strafe Left");
+    assert_on_cell(&game, (0, 3));
+    move_and_finish(&mut game, "Strafe Right");
+    assert_on_cell(&game, (0, 4));
+}
+
+#[test]
+fn releasing_a_held_key_stops_after_the_current_step() {
+    let mut game = game_facing_north();
+    {
+        let mut exposed_config = game.world_mut().resource_mut::<ExposedConfig>();
+        exposed_config.movement.repeat_delay = 0.;
+        exposed_config.movement.repeat_interval = 0.;
+    }
+    let strafe_right = game.binding("Strafe Right");
+
+    game.press(strafe_right);
+    game.run_until("the second step is halfway", |game| game.cell() == (2, 5));
+    game.release(strafe_right);
+    game.finish_movement();
+    assert_on_cell(&game, (2, 5));
+}
+
+#[test]
+fn a_short_hold_does_not_repeat() {
+    let mut game = game_facing_north();
+    let walk_forward = game.binding("Walk Forward");
+
+    game.press(walk_forward);
+    game.run_secs(0.2);
+    game.release(walk_forward);
+    game.finish_movement();
+    assert_on_cell(&game, (1, 3));
+}
+
+#[test]
+fn presses_during_a_step_are_queued() {
+    let mut game = game_facing_north();
+
+    game.tap_action("Walk Forward");
+    game.run_secs(0.1);
+    game.tap_action("Strafe Right");
+    game.finish_movement();
+    assert_on_cell(&game, (1, 4));
+}
+
+#[test]
+fn first_move_snaps_a_free_camera_to_the_closest_direction() {
+    // the camera the default new game starts with isn't marked as oriented to the grid
+    let mut game = TestGame::in_game();
+    let start_cell = game.cell();
+    let start_translation = game.camera_transform().translation;
+    game.set_camera_transform(Transform::from_translation(start_translation).with_rotation(Quat::from_rotation_y(0.6)));
+
+    move_and_finish(&mut game, "Walk Forward");
+    assert_facing(&game, CardinalDirection::North, 0.);
+    assert_on_cell(&game, (start_cell.0 - 1, start_cell.1));
+}
+
+#[test]
+fn a_tap_moves_once_however_the_frames_fall() {
+    // 240 fps runs FixedUpdate only every few frames, 30 fps runs it twice a frame
+    for fps in [240., 30.] {
+        let mut game = game_facing_north();
+        game.set_frame_time(Duration::from_secs_f64(1. / fps));
+
+        move_and_finish(&mut game, "Walk Forward");
+        assert_on_cell(&game, (1, 3));
+    }
+}
+
+#[test]
+fn moves_beyond_the_queue_limit_are_dropped() {
+    // the west end of the test map's one fully open row, with room for six steps east
+    let mut game = TestGame::in_game_with(LaunchOptions {
+        pos: Some((2, 0)),
+        facing: Some(CardinalDirection::North),
+        ..default()
+    });
+    game.world_mut().resource_mut::<ExposedConfig>().movement.max_queued_moves = 2;
+
+    // the first step starts, two more wait behind it, the rest are dropped
+    for _ in 0..6 {
+        game.tap_action("Strafe Right");
+    }
+    game.finish_movement();
+    assert_on_cell(&game, (2, 3));
+}
+
+#[test]
+fn a_queue_limit_of_zero_still_moves_but_queues_nothing() {
+    let mut game = game_facing_north();
+    game.world_mut().resource_mut::<ExposedConfig>().movement.max_queued_moves = 0;
+
+    move_and_finish(&mut game, "Walk Forward");
+    assert_on_cell(&game, (START_CELL.0 - 1, START_CELL.1));
+
+    // the second press comes while the first step is being made, with no room to wait behind it
+    game.tap_action("Walk Backward");
+    game.tap_action("Strafe Right");
+    game.finish_movement();
+    assert_on_cell(&game, START_CELL);
+}
+
+#[test]
+fn two_presses_in_one_tick_while_idle_both_fit_a_queue_limit_of_one() {
+    let mut game = game_facing_north();
+    game.world_mut().resource_mut::<ExposedConfig>().movement.max_queued_moves = 1;
+
+    // the first starts this tick, so only the second waits
+    game.press(game.binding("Walk Forward"));
+    game.press(game.binding("Strafe Right"));
+    game.release(game.binding("Walk Forward"));
+    game.release(game.binding("Strafe Right"));
+    game.update();
+    game.finish_movement();
+    assert_on_cell(&game, (START_CELL.0 - 1, START_CELL.1 + 1));
+}
+
+#[test]
+fn cancel_moves_finishes_only_the_current_step() {
+    let mut game = game_facing_north();
+
+    game.tap_action("Walk Forward");
+    game.tap_action("Strafe Right");
+    game.tap_action("Strafe Right");
+    game.tap_action("Cancel Moves");
+    game.finish_movement();
+    assert_on_cell(&game, (1, 3));
+}
+
+#[test]
+fn free_look_turns_and_tilts_the_camera_until_released() {
+    let mut game = game_facing_north();
+    let free_look = game.binding("Free Look");
+    let max_pitch = game.world().resource::<ExposedConfig>().free_look.max_pitch.to_radians();
+
+    game.press(free_look);
+    // right by less than 45 degrees, and far further down than the camera may tilt
+    game.move_mouse(Vec2::new(200., 5000.));
+    game.update();
+    assert!(yaw(&game.camera_transform()) < -0.1, "didn't turn right");
+    assert!((pitch(&game.camera_transform()) + max_pitch).abs() < ROTATION_TOLERANCE, "tilt wasn't clamped");
+    assert!(!game.movement_data().oriented_to_cardinal_directions);
+
+    game.release(free_look);
+    game.update();
+    game.finish_movement();
+    assert_facing(&game, CardinalDirection::North, 0.);
+    assert!(game.movement_data().oriented_to_cardinal_directions);
+    assert_on_cell(&game, START_CELL);
+}
+
+#[test]
+fn moving_while_looking_around_first_faces_the_closest_direction() {
+    let mut game = game_facing_north();
+    let free_look = game.binding("Free Look");
+
+    game.press(free_look);
+    // most of the way to the right, tilted up
+    let degrees_per_pixel = game.world().resource::<ExposedConfig>().free_look.mouse_sensitivity;
+    game.move_mouse(Vec2::new(70. / degrees_per_pixel, -100.));
+    game.update();
+    move_and_finish(&mut game, "Walk Forward");
+    assert_facing(&game, CardinalDirection::East, -FRAC_PI_2);
+    assert!(pitch(&game.camera_transform()).abs() < ROTATION_TOLERANCE);
+    assert_on_cell(&game, (2, 4));
+
+    // already back on the grid, so letting go doesn't turn again
+    game.release(free_look);
+    game.update();
+    assert!(game.movement_data().command_queue.is_empty());
+}
+
+#[test]
+fn continuous_movement_walks_smoothly_up_to_blocked_tiles() {
+    let mut game = game_facing_north();
+    switch_to_continuous_movement(&mut game);
+    let walk_forward = game.binding("Walk Forward");
+    let start_z = game.camera_transform().translation.z;
+    let grid = game.grid();
+
+    game.press(walk_forward);
+    game.run_secs(0.2);
+    let z = game.camera_transform().translation.z;
+    assert!(z < start_z && z > start_z - grid.cell_size, "walked from z {} to {} rather than part of a cell", start_z, z);
+
+    // (0, 3) is the test map's northmost cell in this column
+    game.run_secs(3.);
+    game.release(walk_forward);
+    game.update();
+    let wall_z = grid.cell_to_world((0, 3), 0.).z - grid.cell_size / 2.;
+    assert!((game.camera_transform().translation.z - (wall_z + PLAYER_RADIUS * grid.cell_size)).abs() < POSITION_TOLERANCE);
+    assert_eq!(game.cell(), (0, 3));
+}
+
+#[test]
+fn leaving_explore_snaps_continuous_movement_to_the_grid() {
+    // the cell north of the test map's shop
+    let mut game = TestGame::in_game_with(LaunchOptions {
+        pos: Some((3, 2)),
+        facing: Some(CardinalDirection::North),
+        ..default()
+    });
+    switch_to_continuous_movement(&mut game);
+
+    // backing onto the shop's cell, turning a little on the way
+    game.press(game.binding("Walk Backward"));
+    game.move_mouse(Vec2::new(100., 0.));
+    game.run_until("the shop is entered", |game| game.ingame_substate() == Some(InGameSubstate::Shop));
+    assert_on_cell(&game, (4, 2));
+    assert_facing(&game, CardinalDirection::North, 0.);
+}
+
+#[test]
+fn eight_way_grids_turn_through_the_diagonals() {
+    let mut game = game_facing_north();
+    switch_to_eight_way_grid(&mut game, 3.);
+
+    move_and_finish(&mut game, "Turn Right");
+    assert_facing(&game, CardinalDirection::NorthEast, -FRAC_PI_4);
+    move_and_finish(&mut game, "Walk Forward");
+    assert_on_cell(&game, (1, 4));
+    move_and_finish(&mut game, "Strafe Right");
+    assert_on_cell(&game, (2, 5));
+    move_and_finish(&mut game, "Turn Left");
+    move_and_finish(&mut game, "Turn Left");
+    assert_facing(&game, CardinalDirection::NorthWest, FRAC_PI_4);
+}
+
+#[test]
+fn free_look_snaps_to_the_closest_direction_of_the_grid() {
+    let mut game = game_facing_north();
+    switch_to_eight_way_grid(&mut game, 5.);
+    let free_look = game.binding("Free Look");
+
+    // 60 degrees right is closest to northeast on this grid, where a four-way grid would face east
+    game.press(free_look);
+    let degrees_per_pixel = game.world().resource::<ExposedConfig>().free_look.mouse_sensitivity;
+    game.move_mouse(Vec2::new(60. / degrees_per_pixel, 0.));
+    game.update();
+    game.release(free_look);
+    game.update();
+    game.finish_movement();
+    assert_facing(&game, CardinalDirection::NorthEast, -FRAC_PI_4);
+}
+
+#[test]
+fn turning_around_faces_the_opposite_way_and_takes_longer_than_a_turn() {
+    let mut game = game_facing_north();
+    let frames_to_finish = |game: &mut TestGame, action: &str| {
+        game.tap_action(action);
+        let mut frames = 0;
+        while !game.movement_data().command_queue.is_empty() {
+            game.update();
+            frames += 1;
+        }
+        frames
+    };
+
+    let turn_frames = frames_to_finish(&mut game, "Turn Right");
+    let turn_around_frames = frames_to_finish(&mut game, "Turn Around");
+    assert_facing(&game, CardinalDirection::West, FRAC_PI_2);
+    assert!(
+        turn_around_frames > turn_frames,
+        "turning around took {} frames, a quarter turn {}", turn_around_frames, turn_frames
+    );
+    assert_on_cell(&game, START_CELL);
+}
+
+#[test]
+fn diagonal_strafes_keep_facing_forward_on_eight_way_grids() {
+    let mut game = game_facing_north();
+    switch_to_eight_way_grid(&mut game, 3.);
+
+    move_and_finish(&mut game, "Strafe Forward Right");
+    assert_on_cell(&game, (1, 4));
+    move_and_finish(&mut game, "Strafe Back Right");
+    assert_on_cell(&game, (2, 5));
+    move_and_finish(&mut game, "Strafe Back Left");
+    assert_on_cell(&game, (3, 4));
+    move_and_finish(&mut game, "Strafe Forward Left");
+    assert_on_cell(&game, START_CELL);
+    assert_facing(&game, CardinalDirection::North, 0.);
+}
+
+#[test]
+fn diagonal_strafes_are_dropped_on_four_way_grids() {
+    let mut game = game_facing_north();
+
+    move_and_finish(&mut game, "Strafe Forward Right");
+    move_and_finish(&mut game, "Strafe Back Left");
+    assert_on_cell(&game, START_CELL);
+    assert_facing(&game, CardinalDirection::North, 0.);
+}
@@ -0,0 +1,200 @@
+// Recording movement input and playing it back: a replay makes the same changes to the movement
+// queue on the same ticks, so it should end exactly where the recording did. Every recording in
+// RECORDINGS_DIRPATH is replayed this way, as a regression corpus - to add one, start the game with
+// --record and copy the file in.
+
+use std::{ collections::HashMap, fs };
+use bevy::prelude::*;
+
+use crate::{
+    launch_options::{ LaunchOptions, LaunchState },
+    plugins::{
+        explore_plugin::{
+            movement::{ CardinalDirection, QueueInput },
+            recording::{ ExplorationRecording, RngSeeds, read_recording },
+        },
+        exposed_config_plugin::ExposedConfig,
+        manage_state_plugin::InGameSubstate,
+    },
+    tests::harness::TestGame,
+};
+
+
+/////////////////////////////////////////
+// CONFIGURABLES
+
+const RECORDINGS_DIRPATH: &str = "src/tests/recordings";
+// recordings the tests make, kept out of the corpus
+const TEST_RECORDINGS_DIRPATH: &str = "target/test_recordings";
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn replay(filepath: &str) -> TestGame {
+    let mut game = TestGame::in_game_with(LaunchOptions {
+        replay: Some(String::from(filepath)),
+        ..default()
+    });
+    game.finish_replay();
+    game
+}
+
+fn assert_ended_where_recorded(game: &TestGame, recording: &ExplorationRecording, filepath: &str) {
+    assert!(
+        recording.ended_at(&game.camera_transform(), game.facing()),
+        "replaying {} ended at {} facing {:?}, the recording at {:?} facing {:?}",
+        filepath, game.camera_transform().translation, game.facing(),
+        recording.end_translation, recording.end_facing
+    );
+}
+
+
+/////////////////////////////////////////
+// TESTS
+
+#[test]
+fn a_recording_replays_to_where_it_ended() {
+    let filepath = format!("{}/replays_to_where_it_ended.ron", TEST_RECORDINGS_DIRPATH);
+    let mut game = TestGame::in_game_with(LaunchOptions {
+        pos: Some((2, 3)),
+        facing: Some(CardinalDirection::North),
+        record: Some(filepath.clone()),
+        ..default()
+    });
+
+    game.tap_action("Walk Forward");
+    game.tap_action("Turn Right");
+    game.finish_movement();
+    game.tap_action("Turn Around");
+    game.finish_movement();
+    // the strafe is queued behind the step, then cancelled
+    game.tap_action("Walk Forward");
+    game.tap_action("Strafe Right");
+    game.tap_action("Cancel Moves");
+    game.finish_movement();
+    game.run_frames(2);
+    assert_eq!(game.cell(), (1, 2));
+    assert_eq!(game.facing(), Some(CardinalDirection::West));
+
+    let recording = read_recording(&filepath).expect("no recording written");
+    assert_eq!(recording.inputs.len(), 6);
+    assert!(recording.inputs.iter().any(|recorded| recorded.input == QueueInput::CancelMoves));
+    assert_ended_where_recorded(&game, &recording, &filepath);
+
+    let replayed = replay(&filepath);
+    assert_eq!(replayed.cell(), (1, 2));
+    assert_ended_where_recorded(&replayed, &recording, &filepath);
+}
+
+#[test]
+fn replaying_ignores_live_movement_input() {
+    let filepath = format!("{}/ignores_live_input.ron", TEST_RECORDINGS_DIRPATH);
+    let mut game = TestGame::in_game_with(LaunchOptions {
+        pos: Some((2, 3)),
+        facing: Some(CardinalDirection::North),
+        record: Some(filepath.clone()),
+        ..default()
+    });
+    game.tap_action("Strafe Right");
+    game.finish_movement();
+    game.run_frames(2);
+    let recording = read_recording(&filepath).expect("no recording written");
+
+    let mut replayed = TestGame::in_game_with(LaunchOptions {
+        replay: Some(filepath.clone()),
+        ..default()
+    });
+    replayed.tap_action("Walk Backward");
+    replayed.tap_action("Turn Left");
+    replayed.finish_replay();
+    assert_eq!(replayed.cell(), (2, 4));
+    assert_ended_where_recorded(&replayed, &recording, &filepath);
+}
+
+#[test]
+fn a_replay_puts_back_the_rng_seeds_it_was_recorded_with() {
+    let filepath = format!("{}/rng_seeds.ron", TEST_RECORDINGS_DIRPATH);
+    let seeds = RngSeeds(HashMap::from([(String::from("encounters"), 1234)]));
+    // the seeds are set before Explore is entered, when the recording starts
+    let mut game = TestGame::new(LaunchOptions {
+        state: Some(LaunchState::InGame),
+        pos: Some((2, 3)),
+        facing: Some(CardinalDirection::North),
+        record: Some(filepath.clone()),
+        ..default()
+    });
+    game.world_mut().insert_resource(seeds.clone());
+    game.run_until("Explore is entered", |game| game.ingame_substate() == Some(InGameSubstate::Explore));
+    game.tap_action("Walk Forward");
+    game.finish_movement();
+    game.run_frames(2);
+    assert_eq!(read_recording(&filepath).expect("no recording written").rng_seeds, seeds.0);
+
+    let replayed = replay(&filepath);
+    assert_eq!(replayed.world().resource::<RngSeeds>(), &seeds);
+}
+
+#[test]
+fn turning_back_to_the_grid_after_free_look_is_recorded() {
+    let filepath = format!("{}/free_look.ron", TEST_RECORDINGS_DIRPATH);
+    let mut game = TestGame::in_game_with(LaunchOptions {
+        pos: Some((2, 3)),
+        facing: Some(CardinalDirection::North),
+        record: Some(filepath.clone()),
+        ..default()
+    });
+    let free_look = game.binding("Free Look");
+
+    // most of the way round to the right, so south once let go
+    game.press(free_look);
+    let degrees_per_pixel = game.world().resource::<ExposedConfig>().free_look.mouse_sensitivity;
+    game.move_mouse(Vec2::new(160. / degrees_per_pixel, 0.));
+    game.update();
+    game.release(free_look);
+    game.update();
+    game.finish_movement();
+    // most of the way left from there, east, this time walking before letting go
+    game.press(free_look);
+    game.move_mouse(Vec2::new(-70. / degrees_per_pixel, 0.));
+    game.update();
+    game.tap_action("Walk Forward");
+    game.release(free_look);
+    game.update();
+    game.finish_movement();
+    game.run_frames(2);
+    assert_eq!(game.cell(), (2, 4));
+    assert_eq!(game.facing(), Some(CardinalDirection::East));
+
+    let recording = read_recording(&filepath).expect("no recording written");
+    let reorientations: Vec<QueueInput> = recording.inputs.iter()
+        .map(|recorded| recorded.input)
+        .filter(|input| matches!(input, QueueInput::Reorient(_)))
+        .collect();
+    assert_eq!(
+        reorientations,
+        [QueueInput::Reorient(CardinalDirection::South), QueueInput::Reorient(CardinalDirection::East)]
+    );
+
+    let replayed = replay(&filepath);
+    assert_ended_where_recorded(&replayed, &recording, &filepath);
+}
+
+#[test]
+fn every_recording_in_the_corpus_replays_to_where_it_ended() {
+    let mut filepaths: Vec<String> = fs::read_dir(RECORDINGS_DIRPATH)
+        .expect("no recordings directory")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    filepaths.sort();
+    assert!(!filepaths.is_empty(), "no recordings in {}", RECORDINGS_DIRPATH);
+
+    for filepath in filepaths {
+        let recording = read_recording(&filepath).unwrap_or_else(|e| panic!("failure reading {}: {}", filepath, e));
+        let game = replay(&filepath);
+        assert_ended_where_recorded(&game, &recording, &filepath);
+    }
+}
@@ -0,0 +1,168 @@
+// Getting from the intro screen into the game through the menus, the way a player would: state
+// changes go through the screen transitions, and buttons are pressed by their label.
+
+use std::collections::HashMap;
+
+use crate::{
+    launch_options::LaunchOptions,
+    plugins::{
+        explore_plugin::{ map::{ DEFAULT_MAP_FILEPATH, Grid }, movement::CardinalDirection },
+        manage_state_plugin::{ GameModeState, InGameSubstate },
+        save_plugin::{ SaveData, SaveSlot, thumbnail::ThumbnailCamera, write_save },
+        transition_plugin::ScreenTransition,
+    },
+    tests::harness::TestGame,
+};
+
+
+/////////////////////////////////////////
+// HELPER FUNCTIONS
+
+fn wait_for_game_mode(game: &mut TestGame, game_mode: GameModeState) {
+    game.run_until(&format!("{:?} is entered", game_mode), |game| game.game_mode() == game_mode);
+}
+
+// Skips the splash sequence, then leaves the title
+fn intro_to_main_menu() -> TestGame {
+    let mut game = TestGame::new(LaunchOptions::default());
+    assert_eq!(game.game_mode(), GameModeState::IntroScreen);
+    game.tap(bevy::prelude::KeyCode::Space);
+    game.tap(bevy::prelude::KeyCode::Space);
+    wait_for_game_mode(&mut game, GameModeState::MainMenu);
+    game
+}
+
+// Input is dropped until the cover has gone
+fn wait_for_transition(game: &mut TestGame) {
+    game.run_until("the transition finishes", |game| !game.world().resource::<ScreenTransition>().is_playing());
+}
+
+fn wait_for_button(game: &mut TestGame, label_key: &str) {
+    game.run_until(&format!("{} is shown", label_key), |game| game.find_button(label_key).is_some());
+}
+
+// Through the pause menu and its confirmation page
+fn quit_to_main_menu(game: &mut TestGame) {
+    game.tap_action("Menu");
+    wait_for_button(game, "pause.quit");
+    game.press_button("pause.quit");
+    wait_for_button(game, "quit.confirm");
+    game.press_button("quit.confirm");
+    wait_for_game_mode(game, GameModeState::MainMenu);
+}
+
+// Through character creation with the default names and classes: Next past every member, then Begin
+fn new_game_from_main_menu(game: &mut TestGame) {
+    game.press_button("menu.new");
+    wait_for_game_mode(game, GameModeState::CharacterCreation);
+    while game.find_button("creation.begin").is_none() {
+        wait_for_button(game, "creation.next");
+        game.press_button("creation.next");
+        // let the next page replace this one
+        game.run_frames(2);
+    }
+    game.press_button("creation.begin");
+    wait_for_game_mode(game, GameModeState::InGame);
+    game.run_until("Explore is entered", |game| game.ingame_substate() == Some(InGameSubstate::Explore));
+    wait_for_transition(game);
+}
+
+fn thumbnail_cameras(game: &mut TestGame) -> usize {
+    game.world_mut().query::<&ThumbnailCamera>().iter(game.world()).count()
+}
+
+
+/////////////////////////////////////////
+// TESTS
+
+#[test]
+fn any_key_leaves_the_intro_for_the_main_menu() {
+    let game = intro_to_main_menu();
+    assert!(game.find_button("menu.new").is_some());
+    assert!(game.find_button("menu.load").is_some());
+}
+
+#[test]
+fn load_menu_returns_to_the_main_menu() {
+    let mut game = intro_to_main_menu();
+
+    game.press_button("menu.load");
+    wait_for_game_mode(&mut game, GameModeState::LoadGameMenu);
+    game.press_button("load.return");
+    wait_for_game_mode(&mut game, GameModeState::MainMenu);
+}
+
+#[test]
+fn loading_a_save_from_the_load_menu_enters_the_game_where_it_was_saved() {
+    let mut game = intro_to_main_menu();
+    let saved_cell = (1, 4);
+    let save = SaveData {
+        slot: SaveSlot::Manual(9),
+        saved_at: 0,
+        character_name: String::from("State Flow Test"),
+        map_filepath: String::from(DEFAULT_MAP_FILEPATH),
+        map_name: String::from("Test Map"),
+        translation: Grid::default().cell_to_world(saved_cell, 5.).to_array(),
+        rotation: bevy::prelude::Quat::from_rotation_y(std::f32::consts::PI).to_array(),
+        cardinal_facing: Some(CardinalDirection::South),
+        flags: HashMap::new(),
+        party: Vec::new()
+    };
+    // the only save in the game's saves directory, so the one the load menu selects
+    write_save(game.saves_dir(), &save).expect("failure writing the test save");
+
+    game.press_button("menu.load");
+    wait_for_game_mode(&mut game, GameModeState::LoadGameMenu);
+    game.press_button("load.load");
+    wait_for_game_mode(&mut game, GameModeState::InGame);
+    game.run_until("the save is applied", |game| game.cell() == saved_cell);
+
+    assert_eq!(game.ingame_substate(), Some(InGameSubstate::Explore));
+    assert_eq!(game.facing(), Some(CardinalDirection::South));
+}
+
+#[test]
+fn quitting_the_game_despawns_thumbnail_captures_that_never_ran() {
+    // without a renderer, the autosave made on entering the map never has its thumbnail captured
+    let mut game = TestGame::in_game();
+    assert_eq!(thumbnail_cameras(&mut game), 1);
+
+    quit_to_main_menu(&mut game);
+    assert_eq!(thumbnail_cameras(&mut game), 0);
+}
+
+#[test]
+fn a_new_game_after_quitting_one_forgets_which_way_it_faced() {
+    let mut game = intro_to_main_menu();
+    new_game_from_main_menu(&mut game);
+    let start_cell = game.cell();
+    assert_eq!(game.facing(), None);
+    game.tap_action("Turn Right");
+    game.finish_movement();
+    assert_eq!(game.facing(), Some(CardinalDirection::East));
+
+    quit_to_main_menu(&mut game);
+    new_game_from_main_menu(&mut game);
+    assert_eq!(game.cell(), start_cell);
+    assert_eq!(game.facing(), None);
+    assert!(!game.movement_data().oriented_to_cardinal_directions);
+
+    // the new game starts facing north, so its first step goes north
+    game.tap_action("Walk Forward");
+    game.finish_movement();
+    assert_eq!(game.facing(), Some(CardinalDirection::North));
+    assert_eq!(game.cell(), (start_cell.0 - 1, start_cell.1));
+}
+
+#[test]
+fn the_back_key_edits_the_name_instead_of_leaving_character_creation() {
+    let mut game = intro_to_main_menu();
+    game.press_button("menu.new");
+    wait_for_game_mode(&mut game, GameModeState::CharacterCreation);
+    wait_for_transition(&mut game);
+
+    game.tap_action("Back");
+    game.run_secs(1.);
+    assert_eq!(game.game_mode(), GameModeState::CharacterCreation);
+    assert!(game.find_button("creation.next").is_some());
+}